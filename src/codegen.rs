@@ -0,0 +1,9 @@
+use crate::{json::ToJsonSchema, rust::ToRust, sql::ToSql, ts::ToTypeScript};
+
+/// The code generation backends a data type is translated by. `DataType`
+/// requires this rather than each backend, so adding a backend doesn't
+/// change the core trait. Implemented for every type that implements all of
+/// them.
+pub trait Codegen: ToJsonSchema + ToRust + ToSql + ToTypeScript {}
+
+impl<T: ToJsonSchema + ToRust + ToSql + ToTypeScript> Codegen for T {}
@@ -0,0 +1,433 @@
+use std::{
+    io::{Read, Write},
+    str::FromStr,
+};
+
+use csv::{QuoteStyle, ReaderBuilder, StringRecord, WriterBuilder};
+use rust_decimal::Decimal;
+
+use crate::{
+    core::{
+        data::{Spreadsheet, Table},
+        schema::{
+            BOOL_TYPE_NAME, DATE_TYPE_NAME, DATETIME_TYPE_NAME, DBL_TYPE_NAME,
+            DEC_TYPE_NAME, DataType, ENUM_TYPE_NAME, INT_TYPE_NAME,
+            LONG_TYPE_NAME, STR_TYPE_NAME, TIME_TYPE_NAME,
+            unique::{Row, UniqueIndex},
+        },
+    },
+    ql::lex::{DATE_FORMAT, DATETIME_FORMAT, Literal, TIME_FORMAT},
+};
+
+/// Converts the text of a CSV cell to a literal of the given type. Strings
+/// and enum variants are taken as-is; any other value is trimmed first.
+pub fn parse_cell(
+    data_type: &dyn DataType,
+    text: &str,
+) -> anyhow::Result<Literal> {
+    let type_name = data_type.get_type_name();
+    let trimmed = text.trim();
+    let lit = match type_name {
+        INT_TYPE_NAME => trimmed.parse().ok().map(Literal::Int),
+        LONG_TYPE_NAME => trimmed.parse().ok().map(Literal::Long),
+        // NaN and infinities can't be compared or written as JSON
+        DBL_TYPE_NAME => trimmed
+            .parse::<f64>()
+            .ok()
+            .filter(|val| val.is_finite())
+            .map(Literal::Dbl),
+        DEC_TYPE_NAME => Decimal::from_str(trimmed).ok().map(Literal::Dec),
+        BOOL_TYPE_NAME => match trimmed.to_lowercase().as_str() {
+            "true" => Some(Literal::Bool(true)),
+            "false" => Some(Literal::Bool(false)),
+            _ => None,
+        },
+        DATE_TYPE_NAME => {
+            Literal::Str(trimmed.into()).get_date().map(Literal::Date)
+        }
+        TIME_TYPE_NAME => {
+            Literal::Str(trimmed.into()).get_time().map(Literal::Time)
+        }
+        DATETIME_TYPE_NAME => Literal::Str(trimmed.into())
+            .get_datetime()
+            .map(Literal::DateTime),
+        _ => Some(Literal::Str(text.into())),
+    };
+    lit.ok_or(anyhow::anyhow!("Couldn't read '{text}' as {type_name}"))
+}
+
+/// Formats a value as the text of a CSV cell, according to its column's type.
+/// Strings are always quoted, doubles are written with as many digits as it
+/// takes to read them back exactly, and nulls are left empty.
+pub fn format_cell(data_type: &dyn DataType, lit: Option<&Literal>) -> String {
+    let Some(lit) = lit else {
+        return String::new();
+    };
+    let formatted = match data_type.get_type_name() {
+        STR_TYPE_NAME | ENUM_TYPE_NAME => lit
+            .get_str()
+            .map(|val| format!("\"{}\"", val.replace('"', "\"\""))),
+        DBL_TYPE_NAME => lit.get_f64().map(|val| val.to_string()),
+        DATE_TYPE_NAME => lit
+            .get_date()
+            .map(|val| val.format(DATE_FORMAT).to_string()),
+        TIME_TYPE_NAME => lit
+            .get_time()
+            .map(|val| val.format(TIME_FORMAT).to_string()),
+        DATETIME_TYPE_NAME => lit
+            .get_datetime()
+            .map(|val| val.format(DATETIME_FORMAT).to_string()),
+        _ => None,
+    };
+    formatted.unwrap_or(lit.to_string())
+}
+
+/// A problem with one row (and possibly one cell) of an imported CSV file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportError {
+    line: u64,
+    column: Option<usize>,
+    message: String,
+}
+
+impl ImportError {
+    pub fn new(line: u64, column: Option<usize>, message: String) -> Self {
+        Self {
+            line,
+            column,
+            message,
+        }
+    }
+
+    /// The line of the file the row starts on, counting from 1.
+    pub fn get_line(&self) -> u64 {
+        self.line
+    }
+
+    /// The position of the bad cell in the row, counting from 1, or `None` if
+    /// the problem isn't with a single cell.
+    pub fn get_column(&self) -> Option<usize> {
+        self.column
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// Summarises a CSV import: how many rows were added to the table and why
+/// the others were rejected.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImportReport {
+    num_imported: usize,
+    errors: Vec<ImportError>,
+}
+
+impl ImportReport {
+    pub fn get_num_imported(&self) -> usize {
+        self.num_imported
+    }
+
+    /// Every problem found, in file order. A rejected row may have several.
+    pub fn get_errors(&self) -> &[ImportError] {
+        &self.errors
+    }
+
+    /// The number of rows that weren't imported.
+    pub fn get_num_rejected(&self) -> usize {
+        let mut lines: Vec<u64> =
+            self.errors.iter().map(|e| e.get_line()).collect();
+        lines.dedup();
+        lines.len()
+    }
+}
+
+// Finds which cells of the CSV record at the start of `raw` are quoted, which
+// the csv reader doesn't keep track of, so that a quoted empty cell can be
+// read as an empty string rather than null.
+fn find_quoted_cells(raw: &[u8]) -> Vec<bool> {
+    let mut quoted = Vec::new();
+    let mut i = 0;
+    loop {
+        let is_quoted = raw.get(i) == Some(&b'"');
+        quoted.push(is_quoted);
+        if is_quoted {
+            // skip to the closing quote, past any escaped ones
+            i += 1;
+            while i < raw.len() {
+                match (raw[i], raw.get(i + 1)) {
+                    (b'"', Some(b'"')) => i += 2,
+                    (b'"', _) => break,
+                    _ => i += 1,
+                }
+            }
+        }
+        while i < raw.len() && !matches!(raw[i], b',' | b'\n' | b'\r') {
+            i += 1;
+        }
+        if raw.get(i) != Some(&b',') {
+            return quoted;
+        }
+        i += 1;
+    }
+}
+
+impl Table {
+    /// Maps each CSV header to the index of its column, failing on unknown
+    /// or repeated headers.
+    fn map_headers(
+        &self,
+        headers: &StringRecord,
+    ) -> anyhow::Result<Vec<usize>> {
+        let mut indices = Vec::new();
+        for header in headers {
+            let idx = self
+                .schema
+                .get_column_index(header.trim())
+                .ok_or(anyhow::anyhow!("Unknown column '{header}'"))?;
+            if indices.contains(&idx) {
+                return Err(anyhow::anyhow!("Repeated column '{header}'"));
+            }
+            indices.push(idx);
+        }
+        Ok(indices)
+    }
+
+    /// Reads a CSV record into a row, returning an error for each cell that
+    /// can't be converted to its column's type. `quoted` says which cells
+    /// were quoted in the file.
+    fn read_record(
+        &self,
+        record: &StringRecord,
+        quoted: &[bool],
+        indices: &[usize],
+        line: u64,
+    ) -> Result<Row, Vec<ImportError>> {
+        let column_names = self.schema.get_column_names();
+        let mut row: Row = vec![None; column_names.len()];
+        let mut errors = Vec::new();
+
+        for (pos, (text, idx)) in record.iter().zip(indices).enumerate() {
+            let column = self.schema.get_column(&column_names[*idx]).unwrap();
+            let is_str = matches!(
+                column.get_type().get_type_name(),
+                STR_TYPE_NAME | ENUM_TYPE_NAME
+            );
+            if text.is_empty() && !(is_str && quoted.get(pos) == Some(&true)) {
+                continue;
+            }
+            match parse_cell(&*column.get_type(), text) {
+                Ok(lit) => row[*idx] = Some(lit),
+                Err(e) => errors.push(ImportError::new(
+                    line,
+                    Some(pos + 1),
+                    format!("Column '{}': {e}", column_names[*idx]),
+                )),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(row)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Imports the rows of a CSV file whose first line names the columns, in
+    /// any order. Columns missing from the file, and empty cells, take the
+    /// column's default or null, except that a quoted empty cell in a str or
+    /// enum column is an empty string, as `export_csv` writes them. Rows that
+    /// don't fit the schema are skipped and listed in the report; only an
+    /// unreadable file or bad headers fail the whole import. Rows are also
+    /// rejected if they duplicate a row already in the table (or imported
+    /// before them) on the primary key or a unique constraint.
+    pub fn import_csv<R: Read>(
+        &mut self,
+        mut reader: R,
+    ) -> anyhow::Result<ImportReport> {
+        // the raw text is kept to find quoted cells
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        let mut reader = ReaderBuilder::new()
+            .flexible(true)
+            .from_reader(data.as_slice());
+        let indices = self.map_headers(reader.headers()?)?;
+        let mut report = ImportReport::default();
+        let mut unique_index = UniqueIndex::new(&self.schema);
+        for (row_idx, row) in self.rows.iter().enumerate() {
+            unique_index.add(row, row_idx);
+        }
+
+        for record in reader.records() {
+            // the whole file has been read, so this is a bad row (e.g. one
+            // that isn't UTF-8) rather than an unreadable file
+            let record = match record {
+                Ok(record) => record,
+                Err(e) => {
+                    let line = e.position().map(|pos| pos.line()).unwrap_or(0);
+                    report.errors.push(ImportError::new(
+                        line,
+                        None,
+                        format!("Couldn't read row: {e}"),
+                    ));
+                    continue;
+                }
+            };
+            let line = record.position().map(|pos| pos.line()).unwrap_or(0);
+
+            if record.len() != indices.len() {
+                report.errors.push(ImportError::new(
+                    line,
+                    None,
+                    format!(
+                        "Expected {} values, got {}",
+                        indices.len(),
+                        record.len()
+                    ),
+                ));
+                continue;
+            }
+
+            let start = record.position().map(|pos| pos.byte()).unwrap_or(0);
+            let quoted = find_quoted_cells(&data[start as usize..]);
+            let mut row =
+                match self.read_record(&record, &quoted, &indices, line) {
+                    Ok(row) => row,
+                    Err(errors) => {
+                        report.errors.extend(errors);
+                        continue;
+                    }
+                };
+            self.apply_defaults(&mut row);
+
+            let violations = self.schema.validate_row(&row);
+            for violation in &violations {
+                let idx = self.schema.get_column_index(violation.get_column());
+                let pos = indices.iter().position(|i| Some(*i) == idx);
+                report.errors.push(ImportError::new(
+                    line,
+                    pos.map(|pos| pos + 1),
+                    format!(
+                        "Column '{}': {}",
+                        violation.get_column(),
+                        violation.get_message()
+                    ),
+                ));
+            }
+            if !violations.is_empty() {
+                continue;
+            }
+
+            // a check that can't be evaluated rejects the row rather than
+            // the whole import
+            let checks = match self.schema.find_check_violations(&row) {
+                Ok(checks) => checks,
+                Err(e) => {
+                    report.errors.push(ImportError::new(
+                        line,
+                        None,
+                        format!("Couldn't evaluate checks: {e}"),
+                    ));
+                    continue;
+                }
+            };
+            for check in &checks {
+                report.errors.push(ImportError::new(
+                    line,
+                    None,
+                    format!("Row violates check '{check}'"),
+                ));
+            }
+            if !checks.is_empty() {
+                continue;
+            }
+
+            let row_idx = self.rows.len();
+            let duplicates = unique_index.find_violations(&row, row_idx);
+            for duplicate in &duplicates {
+                report.errors.push(ImportError::new(
+                    line,
+                    None,
+                    format!(
+                        "Values for ({}) are already in the table",
+                        duplicate.get_columns().join(", ")
+                    ),
+                ));
+            }
+            if !duplicates.is_empty() {
+                continue;
+            }
+
+            unique_index.add(&row, row_idx);
+            self.rows.push(row);
+            report.num_imported += 1;
+        }
+
+        Ok(report)
+    }
+}
+
+impl Table {
+    /// Writes the rows of the table as CSV, with the columns in the order
+    /// they're defined in. If `header` is set, the first line names the
+    /// columns.
+    pub fn export_csv<W: Write>(
+        &self,
+        mut writer: W,
+        header: bool,
+    ) -> anyhow::Result<()> {
+        let column_names = self.schema.get_column_names();
+        if header {
+            let mut header_writer =
+                WriterBuilder::new().from_writer(&mut writer);
+            header_writer
+                .write_record(column_names.iter().map(|name| &**name))?;
+            header_writer.flush()?;
+        }
+
+        // `format_cell` quotes every string, so that an empty string isn't
+        // written as an empty cell, which reads back as null
+        let mut row_writer = WriterBuilder::new()
+            .quote_style(QuoteStyle::Never)
+            .from_writer(writer);
+        let types: Vec<_> = column_names
+            .iter()
+            .map(|name| self.schema.get_column(name).unwrap().get_type())
+            .collect();
+        for row in &self.rows {
+            row_writer.write_record(types.iter().zip(row).map(
+                |(data_type, val)| format_cell(&**data_type, val.as_ref()),
+            ))?;
+        }
+        row_writer.flush()?;
+
+        Ok(())
+    }
+}
+
+impl Spreadsheet {
+    /// Imports a CSV file into the table called `table_name`.
+    pub fn import_csv<R: Read>(
+        &mut self,
+        table_name: &str,
+        reader: R,
+    ) -> anyhow::Result<ImportReport> {
+        self.tables
+            .get_mut(table_name)
+            .ok_or(anyhow::anyhow!("Unknown table '{table_name}'"))?
+            .import_csv(reader)
+    }
+
+    /// Writes the rows of the table called `table_name` as CSV.
+    pub fn export_csv<W: Write>(
+        &self,
+        table_name: &str,
+        writer: W,
+        header: bool,
+    ) -> anyhow::Result<()> {
+        self.tables
+            .get(table_name)
+            .ok_or(anyhow::anyhow!("Unknown table '{table_name}'"))?
+            .export_csv(writer, header)
+    }
+}
@@ -0,0 +1,178 @@
+use std::{collections::HashMap, io::Read, rc::Rc, str::FromStr};
+
+use csv::ReaderBuilder;
+use rust_decimal::Decimal;
+
+use crate::{
+    core::schema::{
+        ColumnSchema, DblDataType, DecDataType, IntDataType, LongDataType,
+        MAX_DEC_PRECISION, SharedDataType, SpreadsheetSchema, StrDataType,
+        TableSchema,
+    },
+    ql::lex::{Token, setup_lexer},
+};
+
+// Takes a CSV header or table name as it is, apart from surrounding spaces,
+// so that the proposed schema can import the file it came from. Names that
+// aren't identifiers are written as strings, which can't hold quotes or line
+// breaks.
+fn to_name(name: &str) -> anyhow::Result<Rc<str>> {
+    let name = name.trim();
+    if name.contains(['"', '\n']) {
+        return Err(anyhow::anyhow!(
+            "Name '{name}' can't contain quotes or line breaks"
+        ));
+    }
+    Ok(name.into())
+}
+
+// The smallest and largest of `values`, if there are any.
+fn bounds<T: PartialOrd + Copy>(values: &[T]) -> (Option<T>, Option<T>) {
+    let min = values
+        .iter()
+        .copied()
+        .reduce(|a, b| if b < a { b } else { a });
+    let max = values
+        .iter()
+        .copied()
+        .reduce(|a, b| if b > a { b } else { a });
+    (min, max)
+}
+
+// Whether a double bound is written in a form the lexer reads back as the
+// same value. Very large or very small doubles aren't.
+fn lexes_back(val: f64) -> bool {
+    let tokens = setup_lexer().lex(&val.to_string()).unwrap_or_default();
+    matches!(
+        tokens.as_slice(),
+        [Token::Literal(lit)] if lit.get_f64() == Some(val)
+    )
+}
+
+// Proposes the narrowest type that fits every non-blank value of a column:
+// int, then long, then dbl, then dec for numbers whose bounds can't be
+// written as doubles, falling back to str.
+fn infer_data_type(values: &[&str], nullable: bool) -> SharedDataType {
+    if !values.is_empty() {
+        if let Some(ints) = values
+            .iter()
+            .map(|val| val.trim().parse::<i32>().ok())
+            .collect::<Option<Vec<_>>>()
+        {
+            let (min, max) = bounds(&ints);
+            return Rc::new(IntDataType::new(min, max, nullable));
+        }
+        if let Some(longs) = values
+            .iter()
+            .map(|val| val.trim().parse::<i64>().ok())
+            .collect::<Option<Vec<_>>>()
+        {
+            let (min, max) = bounds(&longs);
+            return Rc::new(LongDataType::new(min, max, nullable));
+        }
+        if let Some(dbls) = values
+            .iter()
+            .map(|val| val.trim().parse::<f64>().ok().filter(|v| v.is_finite()))
+            .collect::<Option<Vec<_>>>()
+        {
+            let (min, max) = bounds(&dbls);
+            if [min, max].into_iter().flatten().all(lexes_back) {
+                return Rc::new(DblDataType::new(min, max, nullable));
+            }
+        }
+        if let Some(decs) = values
+            .iter()
+            .map(|val| Decimal::from_str(val.trim()).ok())
+            .collect::<Option<Vec<_>>>()
+        {
+            let scale = decs
+                .iter()
+                .map(|val| val.normalize().scale())
+                .max()
+                .unwrap_or(0);
+            let int_digits = decs
+                .iter()
+                .map(|val| match val.trunc().abs() {
+                    int_part if int_part.is_zero() => 0,
+                    int_part => int_part.to_string().len() as u32,
+                })
+                .max()
+                .unwrap_or(0);
+            let precision = (int_digits + scale).max(1);
+            if precision <= MAX_DEC_PRECISION {
+                return Rc::new(DecDataType::new(precision, scale, nullable));
+            }
+        }
+    }
+
+    let lens: Vec<usize> =
+        values.iter().map(|val| val.chars().count()).collect();
+    let (min, max) = bounds(&lens);
+    Rc::new(StrDataType::new(min, max, None, nullable))
+}
+
+/// Proposes a table schema for the CSV file read from `reader`, whose first
+/// line names the columns. Each column gets the narrowest numeric type that
+/// fits its values, or `str`, with bounds taken from the smallest and
+/// largest values seen. Columns with blank cells are made nullable.
+pub fn infer_table_schema<R: Read>(reader: R) -> anyhow::Result<TableSchema> {
+    let mut reader = ReaderBuilder::new().flexible(true).from_reader(reader);
+
+    let mut column_names: Vec<Rc<str>> = Vec::new();
+    for header in reader.headers()? {
+        let name = to_name(header)?;
+        if column_names.contains(&name) {
+            return Err(anyhow::anyhow!("Repeated column '{name}'"));
+        }
+        column_names.push(name);
+    }
+
+    let records: Vec<_> = reader.records().collect::<Result<_, _>>()?;
+    let mut columns = HashMap::new();
+    for (idx, column_name) in column_names.iter().enumerate() {
+        let cells: Vec<Option<&str>> =
+            records.iter().map(|record| record.get(idx)).collect();
+        let nullable = cells
+            .iter()
+            .any(|cell| cell.is_none_or(|text| text.trim().is_empty()));
+        let values: Vec<&str> = cells
+            .into_iter()
+            .flatten()
+            .filter(|text| !text.trim().is_empty())
+            .collect();
+
+        let data_type = infer_data_type(&values, nullable);
+        columns.insert(
+            column_name.clone(),
+            ColumnSchema::new(data_type, None, None, None),
+        );
+    }
+
+    let table = TableSchema::new(columns, column_names, None, vec![], vec![]);
+    table.validate_table_schema()?;
+    Ok(table)
+}
+
+/// Proposes a schema holding a single table called `table_name`, inferred
+/// from the CSV file read from `reader`.
+pub fn infer_schema<R: Read>(
+    table_name: &str,
+    reader: R,
+) -> anyhow::Result<SpreadsheetSchema> {
+    let table_name = to_name(table_name)?;
+    let table = Rc::new(infer_table_schema(reader)?);
+    Ok(SpreadsheetSchema::new(
+        HashMap::from([(table_name.clone(), table)]),
+        vec![table_name],
+        HashMap::new(),
+        vec![],
+    ))
+}
+
+/// Proposes a schema for the CSV file read from `reader`, as DDL.
+pub fn infer_ddl<R: Read>(
+    table_name: &str,
+    reader: R,
+) -> anyhow::Result<String> {
+    Ok(infer_schema(table_name, reader)?.to_string())
+}
@@ -0,0 +1,175 @@
+pub mod csv;
+pub mod infer;
+
+use std::{collections::HashMap, rc::Rc};
+
+use crate::{
+    core::schema::{SharedTableSchema, SpreadsheetSchema, unique::Row},
+    ql::lex::Literal,
+};
+
+/// Holds the rows of a single table, keyed to the schema they conform to.
+/// Inserted rows are checked against column types and check constraints, but
+/// not against unique constraints, which span rows; see
+/// `TableSchema::find_unique_violations`. Imported rows are checked against
+/// all of them.
+pub struct Table {
+    schema: SharedTableSchema,
+    rows: Vec<Row>,
+}
+
+impl Table {
+    pub fn new(schema: SharedTableSchema) -> Self {
+        Self {
+            schema,
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn get_schema(&self) -> SharedTableSchema {
+        self.schema.clone()
+    }
+
+    pub fn get_num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn get_rows(&self) -> &[Row] {
+        &self.rows
+    }
+
+    pub fn get_row(&self, idx: usize) -> Option<&Row> {
+        self.rows.get(idx)
+    }
+
+    /// Gets the value of `column_name` in the row at `idx`, or `None` if the
+    /// row or column doesn't exist or the value is null.
+    pub fn get_value(&self, idx: usize, column_name: &str) -> Option<&Literal> {
+        let col_idx = self.schema.get_column_index(column_name)?;
+        self.rows.get(idx)?.get(col_idx)?.as_ref()
+    }
+
+    // Replaces missing values with the column's default, if it has one.
+    fn apply_defaults(&self, row: &mut Row) {
+        let column_names = self.schema.get_column_names();
+        for (column_name, val) in column_names.iter().zip(row.iter_mut()) {
+            if val.is_none() {
+                let column = self.schema.get_column(column_name).unwrap();
+                *val = column.get_default_value().cloned();
+            }
+        }
+    }
+
+    /// Inserts a row given in column order. Missing values (`None`) are
+    /// replaced by the column's default, if it has one, so a null can't be
+    /// stored in a column with a default this way; use `insert` for that.
+    /// Returns the index of the new row.
+    pub fn insert_row(&mut self, mut row: Row) -> anyhow::Result<usize> {
+        let column_names = self.schema.get_column_names();
+        if row.len() != column_names.len() {
+            return Err(anyhow::anyhow!(
+                "Expected {} values, got {}",
+                column_names.len(),
+                row.len()
+            ));
+        }
+
+        self.apply_defaults(&mut row);
+        self.push_row(row)
+    }
+
+    // Validates a row that already has its defaults applied and stores it.
+    fn push_row(&mut self, row: Row) -> anyhow::Result<usize> {
+        if let Some(violation) = self.schema.validate_row(&row).first() {
+            return Err(anyhow::anyhow!(
+                "Column '{}': {}",
+                violation.get_column(),
+                violation.get_message()
+            ));
+        }
+
+        if let Some(check) = self.schema.find_check_violations(&row)?.first() {
+            return Err(anyhow::anyhow!("Row violates check '{check}'"));
+        }
+
+        self.rows.push(row);
+        Ok(self.rows.len() - 1)
+    }
+
+    /// Inserts a row given as column names and values. Columns that aren't
+    /// given take their default value, or null if they don't have one; a
+    /// column given as `None` is stored as null, even if it has a default.
+    pub fn insert(
+        &mut self,
+        values: HashMap<&str, Option<Literal>>,
+    ) -> anyhow::Result<usize> {
+        let mut row: Row = self
+            .schema
+            .get_column_names()
+            .iter()
+            .map(|name| {
+                let column = self.schema.get_column(name).unwrap();
+                column.get_default_value().cloned()
+            })
+            .collect();
+        for (column_name, val) in values {
+            let idx = self
+                .schema
+                .get_column_index(column_name)
+                .ok_or(anyhow::anyhow!("Unknown column '{column_name}'"))?;
+            row[idx] = val;
+        }
+        self.push_row(row)
+    }
+}
+
+/// Holds a table of rows for each table in a spreadsheet schema.
+pub struct Spreadsheet {
+    tables: HashMap<Rc<str>, Table>,
+    table_names: Vec<Rc<str>>,
+}
+
+impl Spreadsheet {
+    /// Creates a spreadsheet with an empty table for each table in `schema`.
+    pub fn new(schema: &SpreadsheetSchema) -> Self {
+        let table_names = schema.get_table_names().to_vec();
+        let tables = table_names
+            .iter()
+            .map(|name| {
+                (name.clone(), Table::new(schema.get_table(name).unwrap()))
+            })
+            .collect();
+        Self {
+            tables,
+            table_names,
+        }
+    }
+
+    pub fn get_num_tables(&self) -> usize {
+        self.tables.len()
+    }
+
+    pub fn get_table_names(&self) -> &[Rc<str>] {
+        &self.table_names
+    }
+
+    pub fn get_table(&self, name: &str) -> Option<&Table> {
+        self.tables.get(name)
+    }
+
+    pub fn get_table_mut(&mut self, name: &str) -> Option<&mut Table> {
+        self.tables.get_mut(name)
+    }
+
+    /// Inserts a row into the table called `table_name`.
+    pub fn insert(
+        &mut self,
+        table_name: &str,
+        values: HashMap<&str, Option<Literal>>,
+    ) -> anyhow::Result<usize> {
+        self.tables
+            .get_mut(table_name)
+            .ok_or(anyhow::anyhow!("Unknown table '{table_name}'"))?
+            .insert(values)
+    }
+}
@@ -0,0 +1,324 @@
+use std::{cmp::Ordering, rc::Rc};
+
+use crate::{
+    core::schema::{
+        BOOL_TYPE_NAME, DATE_TYPE_NAME, DATETIME_TYPE_NAME, DataType,
+        ENUM_TYPE_NAME, STR_TYPE_NAME, TIME_TYPE_NAME, TableSchema,
+        unique::Row,
+    },
+    error::SchemaError,
+    ql::lex::Literal,
+};
+
+/// A unary operator in a check expression.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnaryOp {
+    Not,
+    Neg,
+}
+
+/// A binary operator in a check expression.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+impl BinaryOp {
+    pub fn is_arithmetic(&self) -> bool {
+        matches!(self, Self::Add | Self::Sub | Self::Mul | Self::Div)
+    }
+
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            Self::Eq | Self::Ne | Self::Lt | Self::Le | Self::Gt | Self::Ge
+        )
+    }
+}
+
+/// An expression in a table-level `check` clause.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Literal(Literal),
+    Column(Rc<str>),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    // the flag is true for `is not null`
+    IsNull(Box<Expr>, bool),
+}
+
+/// The type of a check expression, as inferred from the table's columns.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExprType {
+    Bool,
+    Number,
+    Str,
+    Date,
+    Time,
+    DateTime,
+}
+
+impl ExprType {
+    fn of_literal(lit: &Literal) -> Self {
+        match lit {
+            Literal::Bool(_) => Self::Bool,
+            Literal::Str(_) => Self::Str,
+            Literal::Date(_) => Self::Date,
+            Literal::Time(_) => Self::Time,
+            Literal::DateTime(_) => Self::DateTime,
+            Literal::Int(_)
+            | Literal::Long(_)
+            | Literal::Dbl(_)
+            | Literal::Dec(_) => Self::Number,
+        }
+    }
+
+    fn of_data_type(data_type: &dyn DataType) -> Self {
+        match data_type.get_type_name() {
+            BOOL_TYPE_NAME => Self::Bool,
+            STR_TYPE_NAME | ENUM_TYPE_NAME => Self::Str,
+            DATE_TYPE_NAME => Self::Date,
+            TIME_TYPE_NAME => Self::Time,
+            DATETIME_TYPE_NAME => Self::DateTime,
+            _ => Self::Number,
+        }
+    }
+
+    /// Checks that `expr`, an expression of this type, is of the `expected`
+    /// type.
+    pub fn expect(
+        self,
+        expected: Self,
+        expr: &Expr,
+    ) -> Result<Self, SchemaError> {
+        match self == expected {
+            true => Ok(self),
+            false => Err(SchemaError::InvalidExprType {
+                expr: expr.to_string(),
+                expected: format!("{expected:?}"),
+                found: format!("{self:?}"),
+            }),
+        }
+    }
+}
+
+// Compares two non-null values, using doubles if either side is a double and
+// exact decimals for any other numbers.
+fn compare(l: &Literal, r: &Literal) -> anyhow::Result<Ordering> {
+    let ordering = match (l, r) {
+        (Literal::Dbl(_), _) | (_, Literal::Dbl(_))
+            if l.is_f64() && r.is_f64() =>
+        {
+            l.get_f64().unwrap().partial_cmp(&r.get_f64().unwrap())
+        }
+        _ if l.is_f64() && r.is_f64() => {
+            Some(l.get_dec().unwrap().cmp(&r.get_dec().unwrap()))
+        }
+        (Literal::Str(a), Literal::Str(b)) => Some(a.cmp(b)),
+        (Literal::Bool(a), Literal::Bool(b)) => Some(a.cmp(b)),
+        _ if l.is_date() && r.is_date() => {
+            Some(l.get_date().unwrap().cmp(&r.get_date().unwrap()))
+        }
+        _ if l.is_time() && r.is_time() => {
+            Some(l.get_time().unwrap().cmp(&r.get_time().unwrap()))
+        }
+        _ if l.is_datetime() && r.is_datetime() => {
+            Some(l.get_datetime().unwrap().cmp(&r.get_datetime().unwrap()))
+        }
+        _ => None,
+    };
+    ordering.ok_or(anyhow::anyhow!("Can't compare {l} with {r}"))
+}
+
+// Applies an arithmetic operator to two non-null numbers, using doubles if
+// either side is a double and exact decimals otherwise.
+fn arithmetic(
+    op: BinaryOp,
+    l: &Literal,
+    r: &Literal,
+) -> anyhow::Result<Literal> {
+    if !l.is_f64() || !r.is_f64() {
+        return Err(anyhow::anyhow!("Can't apply arithmetic to {l} and {r}"));
+    }
+
+    if matches!(l, Literal::Dbl(_)) || matches!(r, Literal::Dbl(_)) {
+        let (a, b) = (l.get_f64().unwrap(), r.get_f64().unwrap());
+        let val = match op {
+            BinaryOp::Add => a + b,
+            BinaryOp::Sub => a - b,
+            BinaryOp::Mul => a * b,
+            _ => a / b,
+        };
+        return Ok(Literal::Dbl(val));
+    }
+
+    let (a, b) = (l.get_dec().unwrap(), r.get_dec().unwrap());
+    let val = match op {
+        BinaryOp::Add => a.checked_add(b),
+        BinaryOp::Sub => a.checked_sub(b),
+        BinaryOp::Mul => a.checked_mul(b),
+        _ => a.checked_div(b),
+    };
+    val.map(Literal::Dec).ok_or(anyhow::anyhow!(
+        "Arithmetic overflow or division by zero in {l} and {r}"
+    ))
+}
+
+impl Expr {
+    /// Infers the type of the expression against the columns of `table`,
+    /// failing on unknown columns and mismatched operands.
+    pub fn type_check(
+        &self,
+        table: &TableSchema,
+    ) -> Result<ExprType, SchemaError> {
+        match self {
+            Self::Literal(lit) => Ok(ExprType::of_literal(lit)),
+            Self::Column(name) => match table.get_column(name) {
+                Some(column) => Ok(ExprType::of_data_type(&*column.get_type())),
+                None => Err(SchemaError::UnknownCheckColumn {
+                    column: name.to_string(),
+                }),
+            },
+            Self::Unary(op, expr) => {
+                let expected = match op {
+                    UnaryOp::Not => ExprType::Bool,
+                    UnaryOp::Neg => ExprType::Number,
+                };
+                expr.type_check(table)?.expect(expected, expr)
+            }
+            Self::Binary(op, l, r) => {
+                let (l_type, r_type) =
+                    (l.type_check(table)?, r.type_check(table)?);
+                if l_type != r_type {
+                    return Err(SchemaError::MismatchedOperands {
+                        expr: self.to_string(),
+                        left: format!("{l_type:?}"),
+                        right: format!("{r_type:?}"),
+                    });
+                }
+                if op.is_comparison() {
+                    Ok(ExprType::Bool)
+                } else if op.is_arithmetic() {
+                    l_type.expect(ExprType::Number, l)
+                } else {
+                    l_type.expect(ExprType::Bool, l)
+                }
+            }
+            Self::IsNull(expr, _) => {
+                expr.type_check(table)?;
+                Ok(ExprType::Bool)
+            }
+        }
+    }
+
+    /// Evaluates the expression against a row of `table`. Nulls propagate as
+    /// in SQL, so the result is `None` if the value is unknown.
+    pub fn eval(
+        &self,
+        table: &TableSchema,
+        row: &Row,
+    ) -> anyhow::Result<Option<Literal>> {
+        match self {
+            Self::Literal(lit) => Ok(Some(lit.clone())),
+            Self::Column(name) => {
+                let idx = table.get_column_index(name).ok_or_else(|| {
+                    SchemaError::UnknownCheckColumn {
+                        column: name.to_string(),
+                    }
+                })?;
+                Ok(row.get(idx).cloned().flatten())
+            }
+            Self::Unary(op, expr) => {
+                let Some(val) = expr.eval(table, row)? else {
+                    return Ok(None);
+                };
+                match op {
+                    UnaryOp::Not => match val.get_bool() {
+                        Some(b) => Ok(Some(Literal::Bool(!b))),
+                        None => Err(anyhow::anyhow!("Can't negate {val}")),
+                    },
+                    UnaryOp::Neg => {
+                        arithmetic(BinaryOp::Sub, &Literal::Int(0), &val)
+                            .map(Some)
+                    }
+                }
+            }
+            Self::Binary(op @ (BinaryOp::And | BinaryOp::Or), l, r) => {
+                let get_bool = |val: Option<Literal>| match val {
+                    Some(val) => match val.get_bool() {
+                        Some(b) => Ok(Some(b)),
+                        None => Err(anyhow::anyhow!(
+                            "Expected a boolean, got {val}"
+                        )),
+                    },
+                    None => Ok(None),
+                };
+                let l = get_bool(l.eval(table, row)?)?;
+                let r = get_bool(r.eval(table, row)?)?;
+                // three-valued logic: a known operand can decide the result
+                let result = match (op, l, r) {
+                    (BinaryOp::And, Some(false), _)
+                    | (BinaryOp::And, _, Some(false)) => Some(false),
+                    (BinaryOp::Or, Some(true), _)
+                    | (BinaryOp::Or, _, Some(true)) => Some(true),
+                    (BinaryOp::And, Some(true), Some(true)) => Some(true),
+                    (BinaryOp::Or, Some(false), Some(false)) => Some(false),
+                    _ => None,
+                };
+                Ok(result.map(Literal::Bool))
+            }
+            Self::Binary(op, l, r) => {
+                let (Some(l), Some(r)) =
+                    (l.eval(table, row)?, r.eval(table, row)?)
+                else {
+                    return Ok(None);
+                };
+                if op.is_arithmetic() {
+                    return arithmetic(*op, &l, &r).map(Some);
+                }
+                let ordering = compare(&l, &r)?;
+                let result = match op {
+                    BinaryOp::Eq => ordering.is_eq(),
+                    BinaryOp::Ne => ordering.is_ne(),
+                    BinaryOp::Lt => ordering.is_lt(),
+                    BinaryOp::Le => ordering.is_le(),
+                    BinaryOp::Gt => ordering.is_gt(),
+                    _ => ordering.is_ge(),
+                };
+                Ok(Some(Literal::Bool(result)))
+            }
+            Self::IsNull(expr, negated) => {
+                let is_null = expr.eval(table, row)?.is_none();
+                Ok(Some(Literal::Bool(is_null != *negated)))
+            }
+        }
+    }
+}
+
+impl TableSchema {
+    /// Evaluates every check against `row`, returning the ones that failed.
+    /// As in SQL, a check whose result is unknown (null) passes.
+    pub fn find_check_violations(
+        &self,
+        row: &Row,
+    ) -> anyhow::Result<Vec<&Expr>> {
+        let mut violations = Vec::new();
+        for check in &self.checks {
+            if check.eval(self, row)? == Some(Literal::Bool(false)) {
+                violations.push(check);
+            }
+        }
+        Ok(violations)
+    }
+}
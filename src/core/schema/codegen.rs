@@ -1,13 +1,30 @@
-use std::fmt::Display;
+use std::{fmt::Display, rc::Rc};
 
 use crate::{
     core::schema::{
-        ColumnSchema, DBL_TYPE_NAME, DblDataType, INT_TYPE_NAME, IntDataType,
-        STR_TYPE_NAME, SpreadsheetSchema, StrDataType, TableSchema,
+        BOOL_TYPE_NAME, BoolDataType, ColumnSchema, DATE_TYPE_NAME,
+        DATETIME_TYPE_NAME, DBL_TYPE_NAME, DEC_TYPE_NAME, DateDataType,
+        DateTimeDataType, DblDataType, DecDataType, ENUM_TYPE_NAME,
+        EnumDataType, ForeignKey, INT_TYPE_NAME, IntDataType, LONG_TYPE_NAME,
+        LongDataType, STR_TYPE_NAME, SpreadsheetSchema, StrDataType,
+        TIME_TYPE_NAME, TableSchema, TimeDataType,
+        check::{BinaryOp, Expr, UnaryOp},
+    },
+    ql::{
+        Stmt,
+        lex::{DATE_FORMAT, DATETIME_FORMAT, Literal, TIME_FORMAT, quote_name},
     },
-    ql::Stmt,
 };
 
+// Formats a literal as it's written in a schema. `Literal`'s own `Display`
+// leaves strings unquoted, for messages and cells.
+fn format_literal(literal: &Literal) -> String {
+    match literal {
+        Literal::Str(val) => format!("\"{val}\""),
+        _ => literal.to_string(),
+    }
+}
+
 impl Display for IntDataType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let min = match self.min {
@@ -31,6 +48,29 @@ impl Display for IntDataType {
     }
 }
 
+impl Display for LongDataType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let min = match self.min {
+            Some(val) => &val.to_string(),
+            None => "",
+        };
+
+        let max = match self.max {
+            Some(val) => &val.to_string(),
+            None => "",
+        };
+
+        write!(
+            f,
+            "{}<{}, {}>{}",
+            LONG_TYPE_NAME,
+            min,
+            max,
+            if self.nullable { "?" } else { "" }
+        )
+    }
+}
+
 impl Display for DblDataType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let min = match self.min {
@@ -54,6 +94,19 @@ impl Display for DblDataType {
     }
 }
 
+impl Display for DecDataType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}<{}, {}>{}",
+            DEC_TYPE_NAME,
+            self.precision,
+            self.scale,
+            if self.nullable { "?" } else { "" }
+        )
+    }
+}
+
 impl Display for StrDataType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let min = match self.min {
@@ -66,43 +119,309 @@ impl Display for StrDataType {
             None => "",
         };
 
+        let pattern = match &self.pattern {
+            Some(pattern) => &format!(" ~ \"{pattern}\""),
+            None => "",
+        };
+
         write!(
             f,
-            "{}<{}, {}>{}",
+            "{}<{}, {}>{}{}",
             STR_TYPE_NAME,
             min,
             max,
+            pattern,
+            if self.nullable { "?" } else { "" }
+        )
+    }
+}
+
+impl Display for BoolDataType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            BOOL_TYPE_NAME,
+            if self.nullable { "?" } else { "" }
+        )
+    }
+}
+
+impl Display for DateDataType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let min = match self.min {
+            Some(val) => &val.format(DATE_FORMAT).to_string(),
+            None => "",
+        };
+
+        let max = match self.max {
+            Some(val) => &val.format(DATE_FORMAT).to_string(),
+            None => "",
+        };
+
+        write!(
+            f,
+            "{}<{}, {}>{}",
+            DATE_TYPE_NAME,
+            min,
+            max,
             if self.nullable { "?" } else { "" }
         )
     }
 }
 
+impl Display for TimeDataType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let min = match self.min {
+            Some(val) => &val.format(TIME_FORMAT).to_string(),
+            None => "",
+        };
+
+        let max = match self.max {
+            Some(val) => &val.format(TIME_FORMAT).to_string(),
+            None => "",
+        };
+
+        write!(
+            f,
+            "{}<{}, {}>{}",
+            TIME_TYPE_NAME,
+            min,
+            max,
+            if self.nullable { "?" } else { "" }
+        )
+    }
+}
+
+impl Display for DateTimeDataType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let min = match self.min {
+            Some(val) => &val.format(DATETIME_FORMAT).to_string(),
+            None => "",
+        };
+
+        let max = match self.max {
+            Some(val) => &val.format(DATETIME_FORMAT).to_string(),
+            None => "",
+        };
+
+        write!(
+            f,
+            "{}<{}, {}>{}",
+            DATETIME_TYPE_NAME,
+            min,
+            max,
+            if self.nullable { "?" } else { "" }
+        )
+    }
+}
+
+impl Display for EnumDataType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let variant_strs: Vec<String> = self
+            .variants
+            .iter()
+            .map(|variant| format!("\"{variant}\""))
+            .collect();
+
+        write!(
+            f,
+            "{}({}){}",
+            ENUM_TYPE_NAME,
+            variant_strs.join(", "),
+            if self.nullable { "?" } else { "" }
+        )
+    }
+}
+
+impl Display for UnaryOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Not => write!(f, "not "),
+            Self::Neg => write!(f, "-"),
+        }
+    }
+}
+
+impl Display for BinaryOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Eq => "=",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::And => "and",
+            Self::Or => "or",
+        };
+        write!(f, "{op}")
+    }
+}
+
+impl Expr {
+    // how tightly the expression binds, used to decide where parentheses are
+    // needed for the output to parse back to the same tree
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Binary(BinaryOp::Or, _, _) => 1,
+            Self::Binary(BinaryOp::And, _, _) => 2,
+            Self::Unary(UnaryOp::Not, _) => 3,
+            Self::Binary(op, _, _) if op.is_comparison() => 4,
+            Self::IsNull(_, _) => 4,
+            Self::Binary(BinaryOp::Add | BinaryOp::Sub, _, _) => 5,
+            Self::Binary(_, _, _) => 6,
+            Self::Unary(UnaryOp::Neg, _) => 7,
+            Self::Literal(_) | Self::Column(_) => 8,
+        }
+    }
+
+    fn fmt_operand(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        parenthesize: bool,
+    ) -> std::fmt::Result {
+        match parenthesize {
+            true => write!(f, "({self})"),
+            false => write!(f, "{self}"),
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let precedence = self.precedence();
+        match self {
+            Self::Literal(literal) => write!(f, "{}", format_literal(literal)),
+            Self::Column(name) => write!(f, "{name}"),
+            Self::Unary(op, expr) => {
+                write!(f, "{op}")?;
+                // `--` would read as a comment in SQL, so a negative operand
+                // is parenthesized
+                let negated_minus =
+                    *op == UnaryOp::Neg && expr.to_string().starts_with('-');
+                expr.fmt_operand(
+                    f,
+                    expr.precedence() < precedence || negated_minus,
+                )
+            }
+            Self::Binary(op, l, r) => {
+                // comparisons don't chain, so neither side can be one
+                let l_parens = match op.is_comparison() {
+                    true => l.precedence() <= precedence,
+                    false => l.precedence() < precedence,
+                };
+                l.fmt_operand(f, l_parens)?;
+                write!(f, " {op} ")?;
+                r.fmt_operand(f, r.precedence() <= precedence)
+            }
+            Self::IsNull(expr, negated) => {
+                expr.fmt_operand(f, expr.precedence() <= precedence)?;
+                match negated {
+                    true => write!(f, " is not null"),
+                    false => write!(f, " is null"),
+                }
+            }
+        }
+    }
+}
+
+// Joins a list of column names, quoting them where needed.
+fn quote_names(names: &[Rc<str>]) -> String {
+    names
+        .iter()
+        .map(|name| quote_name(name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl Display for ForeignKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "references {}({})",
+            quote_name(&self.table),
+            quote_name(&self.column)
+        )
+    }
+}
+
 impl Display for ColumnSchema {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match &self.default_value {
-            Some(val) => write!(f, "{} = {}", self.column_type, val),
-            None => write!(f, "{}", self.column_type),
+        // the alternate form refers to named types by name
+        match (&self.type_alias, f.alternate()) {
+            (Some(alias), true) => write!(f, "{}", quote_name(alias))?,
+            _ => write!(f, "{}", self.column_type)?,
+        }
+        if let Some(val) = &self.default_value {
+            write!(f, " = {}", format_literal(val))?;
+        }
+        match &self.references {
+            Some(fk) => write!(f, " {fk}"),
+            None => Ok(()),
         }
     }
 }
 
 impl Display for TableSchema {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let column_strs: Vec<String> = self
+        let mut column_strs: Vec<String> = self
             .column_names
             .iter()
-            .map(|name| format!("{name}: {}", self.columns[name]))
+            .map(|name| match f.alternate() {
+                true => {
+                    format!("{}: {:#}", quote_name(name), self.columns[name])
+                }
+                false => {
+                    format!("{}: {}", quote_name(name), self.columns[name])
+                }
+            })
             .collect();
+        if let Some(key) = &self.primary_key {
+            column_strs.push(format!("primary key ({})", quote_names(key)));
+        }
+        for columns in &self.unique {
+            column_strs.push(format!("unique ({})", quote_names(columns)));
+        }
+        for check in &self.checks {
+            column_strs.push(format!("check {check}"));
+        }
         let columns_str = column_strs.join(", ");
 
         write!(f, "({})", columns_str)
     }
 }
 
+/// The alternate form (`{:#}`) keeps the schema's `type` statements and
+/// refers to named types by name, so that it parses back to the same schema.
+/// Otherwise named types are written out in full.
 impl Display for SpreadsheetSchema {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for name in &self.table_names {
-            writeln!(f, "table {name} {};", self.tables[name])?;
+        if f.alternate() {
+            for name in &self.type_names {
+                writeln!(f, "type {} {};", quote_name(name), self.types[name])?;
+            }
+            for name in &self.table_names {
+                writeln!(
+                    f,
+                    "table {} {:#};",
+                    quote_name(name),
+                    self.tables[name]
+                )?;
+            }
+        } else {
+            for name in &self.table_names {
+                writeln!(
+                    f,
+                    "table {} {};",
+                    quote_name(name),
+                    self.tables[name]
+                )?;
+            }
         }
         Ok(())
     }
@@ -112,10 +431,10 @@ impl Display for Stmt {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::TableSchema(name, schema) => {
-                write!(f, "table {name} {schema};",)
+                write!(f, "table {} {schema};", quote_name(name))
             }
             Self::TypeDef(type_name, data_type) => {
-                write!(f, "type {type_name} {data_type};")
+                write!(f, "type {} {data_type};", quote_name(type_name))
             }
         }
     }
@@ -1,12 +1,27 @@
 use crate::{
     core::schema::{
-        ColumnSchema, DBL_TYPE_NAME, DblDataType, INT_TYPE_NAME, IntDataType,
-        STR_TYPE_NAME, SpreadsheetSchema, StrDataType, TableSchema,
+        BOOL_TYPE_NAME, BoolDataType, ColumnSchema, DATE_TYPE_NAME,
+        DATETIME_TYPE_NAME, DBL_TYPE_NAME, DEC_TYPE_NAME, DataType,
+        DateDataType, DateTimeDataType, DblDataType, DecDataType,
+        ENUM_TYPE_NAME, EnumDataType, ForeignKey, INT_TYPE_NAME, IntDataType,
+        LONG_TYPE_NAME, LongDataType, STR_TYPE_NAME, SharedDataType,
+        SpreadsheetSchema, StrDataType, TIME_TYPE_NAME, TableSchema,
+        TimeDataType, check::Expr,
+    },
+    error::SchemaError,
+    json::{FromJson, ToJson},
+    ql::{
+        lex::{
+            DATE_FORMAT, DATETIME_FORMAT, Literal, TIME_FORMAT, setup_lexer,
+        },
+        parse::Parse,
     },
-    json::ToJson,
-    ql::lex::Literal,
 };
+use regex::Regex;
+use rlrl::parse::TokenQueue;
+use rust_decimal::Decimal;
 use serde_json::{Number, Value, json};
+use std::{collections::HashMap, rc::Rc, str::FromStr};
 
 impl ToJson for Literal {
     fn to_json(&self) -> Value {
@@ -14,8 +29,16 @@ impl ToJson for Literal {
             Self::Int(val) => {
                 Value::Number(Number::from_i128(*val as i128).unwrap())
             }
+            Self::Long(val) => Value::Number(Number::from(*val)),
             Self::Dbl(val) => Value::Number(Number::from_f64(*val).unwrap()),
+            // written as a string, since JSON numbers are usually read as
+            // doubles and would lose digits
+            Self::Dec(val) => Value::String(val.to_string()),
             Self::Str(val) => Value::String(val.to_string()),
+            Self::Bool(val) => Value::Bool(*val),
+            Self::Date(_) | Self::Time(_) | Self::DateTime(_) => {
+                Value::String(self.to_string())
+            }
         }
     }
 }
@@ -26,33 +49,90 @@ impl ToJson for IntDataType {
     }
 }
 
+impl ToJson for LongDataType {
+    fn to_json(&self) -> Value {
+        json!({"super": LONG_TYPE_NAME, "nullable": self.nullable, "min": self.min, "max": self.max})
+    }
+}
+
 impl ToJson for DblDataType {
     fn to_json(&self) -> Value {
         json!({"super": DBL_TYPE_NAME, "nullable": self.nullable, "min": self.min, "max": self.max})
     }
 }
 
+impl ToJson for DecDataType {
+    fn to_json(&self) -> Value {
+        json!({"super": DEC_TYPE_NAME, "nullable": self.nullable, "precision": self.precision, "scale": self.scale})
+    }
+}
+
 impl ToJson for StrDataType {
     fn to_json(&self) -> Value {
-        json!({"super": STR_TYPE_NAME, "nullable": self.nullable, "min": self.min, "max": self.max})
+        let pattern = self.pattern.as_ref().map(|pattern| pattern.as_str());
+        json!({"super": STR_TYPE_NAME, "nullable": self.nullable, "min": self.min, "max": self.max, "pattern": pattern})
+    }
+}
+
+impl ToJson for BoolDataType {
+    fn to_json(&self) -> Value {
+        json!({"super": BOOL_TYPE_NAME, "nullable": self.nullable})
+    }
+}
+
+impl ToJson for DateDataType {
+    fn to_json(&self) -> Value {
+        let min = self.min.map(|val| val.format(DATE_FORMAT).to_string());
+        let max = self.max.map(|val| val.format(DATE_FORMAT).to_string());
+        json!({"super": DATE_TYPE_NAME, "nullable": self.nullable, "min": min, "max": max})
+    }
+}
+
+impl ToJson for TimeDataType {
+    fn to_json(&self) -> Value {
+        let min = self.min.map(|val| val.format(TIME_FORMAT).to_string());
+        let max = self.max.map(|val| val.format(TIME_FORMAT).to_string());
+        json!({"super": TIME_TYPE_NAME, "nullable": self.nullable, "min": min, "max": max})
+    }
+}
+
+impl ToJson for DateTimeDataType {
+    fn to_json(&self) -> Value {
+        let min = self.min.map(|val| val.format(DATETIME_FORMAT).to_string());
+        let max = self.max.map(|val| val.format(DATETIME_FORMAT).to_string());
+        json!({"super": DATETIME_TYPE_NAME, "nullable": self.nullable, "min": min, "max": max})
+    }
+}
+
+impl ToJson for EnumDataType {
+    fn to_json(&self) -> Value {
+        let variants: Vec<&str> =
+            self.variants.iter().map(|variant| &**variant).collect();
+        json!({"super": ENUM_TYPE_NAME, "nullable": self.nullable, "variants": variants})
+    }
+}
+
+impl ToJson for ForeignKey {
+    fn to_json(&self) -> Value {
+        json!({"table": &*self.table, "column": &*self.column})
     }
 }
 
 impl ToJson for ColumnSchema {
     fn to_json(&self) -> Value {
-        match &self.default_value {
-            Some(val) => {
-                json!({
-                    "column_type": self.column_type.to_json(),
-                    "default_value": val.to_json()
-                })
-            }
-            None => {
-                json!({
-                    "column_type": self.column_type.to_json()
-                })
-            }
+        let mut json = json!({
+            "column_type": self.column_type.to_json()
+        });
+        if let Some(alias) = &self.type_alias {
+            json["type_alias"] = Value::from(&**alias);
+        }
+        if let Some(val) = &self.default_value {
+            json["default_value"] = val.to_json();
         }
+        if let Some(fk) = &self.references {
+            json["references"] = fk.to_json();
+        }
+        json
     }
 }
 
@@ -64,7 +144,14 @@ impl ToJson for TableSchema {
                     .iter()
                     .map(|name| (name.to_string(), self.columns[name].to_json()))
                     .collect::<serde_json::Map<String, Value>>(),
-            )
+            ),
+            "primary_key": self.primary_key.as_ref().map(|key| {
+                key.iter().map(|name| name.to_string()).collect::<Vec<String>>()
+            }),
+            "unique": self.unique.iter().map(|columns| {
+                columns.iter().map(|name| name.to_string()).collect::<Vec<String>>()
+            }).collect::<Vec<Vec<String>>>(),
+            "checks": self.checks.iter().map(|check| check.to_string()).collect::<Vec<String>>()
         })
     }
 }
@@ -72,6 +159,10 @@ impl ToJson for TableSchema {
 impl ToJson for SpreadsheetSchema {
     fn to_json(&self) -> Value {
         json!({
+            "types": Value::Object(self.type_names
+                .iter()
+                .map(|name| (name.to_string(), self.types[name].to_json()))
+                .collect::<serde_json::Map<String, Value>>()),
             "tables": Value::Object(self.table_names
                 .iter()
                 .map(|name| (name.to_string(), self.tables[name].to_json()))
@@ -79,3 +170,344 @@ impl ToJson for SpreadsheetSchema {
         })
     }
 }
+
+// Gets a field that must be present.
+fn get_field<'a>(json: &'a Value, key: &str) -> anyhow::Result<&'a Value> {
+    json.get(key)
+        .ok_or(anyhow::anyhow!("Missing field '{key}' in {json}"))
+}
+
+// Gets a field that may be missing or null, converting it with `convert`.
+fn get_optional<'a, T>(
+    json: &'a Value,
+    key: &str,
+    convert: impl Fn(&'a Value) -> Option<T>,
+) -> anyhow::Result<Option<T>> {
+    match json.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(val) => convert(val)
+            .map(Some)
+            .ok_or(anyhow::anyhow!("Invalid value {val} for field '{key}'")),
+    }
+}
+
+// Gets a field that must be present, converting it with `convert`.
+fn get_required<'a, T>(
+    json: &'a Value,
+    key: &str,
+    convert: impl Fn(&'a Value) -> Option<T>,
+) -> anyhow::Result<T> {
+    get_optional(json, key, convert)?
+        .ok_or(anyhow::anyhow!("Missing field '{key}' in {json}"))
+}
+
+// Reads a list of names, e.g. the columns of a unique constraint.
+fn get_names(json: &Value) -> Option<Vec<Rc<str>>> {
+    json.as_array()?
+        .iter()
+        .map(|name| name.as_str().map(Rc::from))
+        .collect()
+}
+
+fn get_i32(json: &Value) -> Option<i32> {
+    json.as_i64()?.try_into().ok()
+}
+
+fn get_u32(json: &Value) -> Option<u32> {
+    json.as_u64()?.try_into().ok()
+}
+
+fn get_usize(json: &Value) -> Option<usize> {
+    json.as_u64()?.try_into().ok()
+}
+
+// Reads a date, time or datetime from the string that `ToJson` emits.
+fn get_temporal<T>(json: &Value, get: fn(&Literal) -> Option<T>) -> Option<T> {
+    get(&Literal::Str(json.as_str()?.into()))
+}
+
+/// Reads a value of the given type from the JSON that `Literal::to_json`
+/// emits for it.
+pub fn literal_from_json(
+    data_type: &dyn DataType,
+    json: &Value,
+) -> anyhow::Result<Literal> {
+    let lit = match data_type.get_type_name() {
+        INT_TYPE_NAME => get_i32(json).map(Literal::Int),
+        LONG_TYPE_NAME => json.as_i64().map(Literal::Long),
+        DBL_TYPE_NAME => json.as_f64().map(Literal::Dbl),
+        // numbers are still accepted for JSON written by hand
+        DEC_TYPE_NAME => match json {
+            Value::String(val) => Decimal::from_str(val).ok(),
+            Value::Number(val) => Decimal::from_str(&val.to_string()).ok(),
+            _ => None,
+        }
+        .map(Literal::Dec),
+        BOOL_TYPE_NAME => json.as_bool().map(Literal::Bool),
+        DATE_TYPE_NAME => {
+            get_temporal(json, Literal::get_date).map(Literal::Date)
+        }
+        TIME_TYPE_NAME => {
+            get_temporal(json, Literal::get_time).map(Literal::Time)
+        }
+        DATETIME_TYPE_NAME => {
+            get_temporal(json, Literal::get_datetime).map(Literal::DateTime)
+        }
+        _ => json.as_str().map(|val| Literal::Str(val.into())),
+    };
+    lit.ok_or(anyhow::anyhow!(
+        "Couldn't read {json} as a {} value",
+        data_type.get_type_name()
+    ))
+}
+
+impl FromJson for IntDataType {
+    fn from_json(json: &Value) -> anyhow::Result<Self> {
+        Ok(Self::new(
+            get_optional(json, "min", get_i32)?,
+            get_optional(json, "max", get_i32)?,
+            get_required(json, "nullable", Value::as_bool)?,
+        ))
+    }
+}
+
+impl FromJson for LongDataType {
+    fn from_json(json: &Value) -> anyhow::Result<Self> {
+        Ok(Self::new(
+            get_optional(json, "min", Value::as_i64)?,
+            get_optional(json, "max", Value::as_i64)?,
+            get_required(json, "nullable", Value::as_bool)?,
+        ))
+    }
+}
+
+impl FromJson for DblDataType {
+    fn from_json(json: &Value) -> anyhow::Result<Self> {
+        Ok(Self::new(
+            get_optional(json, "min", Value::as_f64)?,
+            get_optional(json, "max", Value::as_f64)?,
+            get_required(json, "nullable", Value::as_bool)?,
+        ))
+    }
+}
+
+impl FromJson for DecDataType {
+    fn from_json(json: &Value) -> anyhow::Result<Self> {
+        Ok(Self::new(
+            get_required(json, "precision", get_u32)?,
+            get_required(json, "scale", get_u32)?,
+            get_required(json, "nullable", Value::as_bool)?,
+        ))
+    }
+}
+
+impl FromJson for StrDataType {
+    fn from_json(json: &Value) -> anyhow::Result<Self> {
+        let pattern = match get_optional(json, "pattern", Value::as_str)? {
+            Some(pattern) => Some(Regex::new(pattern)?),
+            None => None,
+        };
+        Ok(Self::new(
+            get_optional(json, "min", get_usize)?,
+            get_optional(json, "max", get_usize)?,
+            pattern,
+            get_required(json, "nullable", Value::as_bool)?,
+        ))
+    }
+}
+
+impl FromJson for BoolDataType {
+    fn from_json(json: &Value) -> anyhow::Result<Self> {
+        Ok(Self::new(get_required(json, "nullable", Value::as_bool)?))
+    }
+}
+
+impl FromJson for DateDataType {
+    fn from_json(json: &Value) -> anyhow::Result<Self> {
+        let get = |val: &Value| get_temporal(val, Literal::get_date);
+        Ok(Self::new(
+            get_optional(json, "min", get)?,
+            get_optional(json, "max", get)?,
+            get_required(json, "nullable", Value::as_bool)?,
+        ))
+    }
+}
+
+impl FromJson for TimeDataType {
+    fn from_json(json: &Value) -> anyhow::Result<Self> {
+        let get = |val: &Value| get_temporal(val, Literal::get_time);
+        Ok(Self::new(
+            get_optional(json, "min", get)?,
+            get_optional(json, "max", get)?,
+            get_required(json, "nullable", Value::as_bool)?,
+        ))
+    }
+}
+
+impl FromJson for DateTimeDataType {
+    fn from_json(json: &Value) -> anyhow::Result<Self> {
+        let get = |val: &Value| get_temporal(val, Literal::get_datetime);
+        Ok(Self::new(
+            get_optional(json, "min", get)?,
+            get_optional(json, "max", get)?,
+            get_required(json, "nullable", Value::as_bool)?,
+        ))
+    }
+}
+
+impl FromJson for EnumDataType {
+    fn from_json(json: &Value) -> anyhow::Result<Self> {
+        Ok(Self::new(
+            get_required(json, "variants", get_names)?,
+            get_required(json, "nullable", Value::as_bool)?,
+        ))
+    }
+}
+
+/// Reads any data type, choosing the type from the `super` field.
+impl FromJson for SharedDataType {
+    fn from_json(json: &Value) -> anyhow::Result<Self> {
+        let type_name = get_required(json, "super", Value::as_str)?;
+        let data_type: SharedDataType = match type_name {
+            INT_TYPE_NAME => Rc::new(IntDataType::from_json(json)?),
+            LONG_TYPE_NAME => Rc::new(LongDataType::from_json(json)?),
+            DBL_TYPE_NAME => Rc::new(DblDataType::from_json(json)?),
+            DEC_TYPE_NAME => Rc::new(DecDataType::from_json(json)?),
+            STR_TYPE_NAME => Rc::new(StrDataType::from_json(json)?),
+            BOOL_TYPE_NAME => Rc::new(BoolDataType::from_json(json)?),
+            DATE_TYPE_NAME => Rc::new(DateDataType::from_json(json)?),
+            TIME_TYPE_NAME => Rc::new(TimeDataType::from_json(json)?),
+            DATETIME_TYPE_NAME => Rc::new(DateTimeDataType::from_json(json)?),
+            ENUM_TYPE_NAME => Rc::new(EnumDataType::from_json(json)?),
+            _ => {
+                return Err(SchemaError::UnknownType {
+                    name: type_name.to_string(),
+                }
+                .into());
+            }
+        };
+        Ok(data_type)
+    }
+}
+
+impl FromJson for ForeignKey {
+    fn from_json(json: &Value) -> anyhow::Result<Self> {
+        Ok(Self::new(
+            get_required(json, "table", Value::as_str)?.into(),
+            get_required(json, "column", Value::as_str)?.into(),
+        ))
+    }
+}
+
+impl FromJson for ColumnSchema {
+    fn from_json(json: &Value) -> anyhow::Result<Self> {
+        let column_type =
+            SharedDataType::from_json(get_field(json, "column_type")?)?;
+        let default_value = match json.get("default_value") {
+            None | Some(Value::Null) => None,
+            Some(val) => Some(literal_from_json(&*column_type, val)?),
+        };
+        let references = match json.get("references") {
+            None | Some(Value::Null) => None,
+            Some(val) => Some(ForeignKey::from_json(val)?),
+        };
+        let type_alias = get_optional(json, "type_alias", Value::as_str)?;
+        Ok(Self::new(
+            column_type,
+            type_alias.map(Rc::from),
+            default_value,
+            references,
+        ))
+    }
+}
+
+/// Reads a check from the expression text that `ToJson` emits.
+impl FromJson for Expr {
+    fn from_json(json: &Value) -> anyhow::Result<Self> {
+        let text = json.as_str().ok_or(anyhow::anyhow!(
+            "Expected a check expression, got {json}"
+        ))?;
+        let mut tq = TokenQueue::from(setup_lexer().lex(text)?);
+        let expr = tq.parse_with_mut(Expr::parse, &mut HashMap::new())?;
+        if !tq.is_consumed() {
+            return Err(anyhow::anyhow!("Couldn't parse check '{text}'"));
+        }
+        Ok(expr)
+    }
+}
+
+impl FromJson for TableSchema {
+    fn from_json(json: &Value) -> anyhow::Result<Self> {
+        let columns_json = get_required(json, "columns", Value::as_object)?;
+        let mut columns = HashMap::new();
+        let mut column_names = Vec::new();
+        for (name, column) in columns_json {
+            let name: Rc<str> = name.as_str().into();
+            columns.insert(name.clone(), ColumnSchema::from_json(column)?);
+            column_names.push(name);
+        }
+
+        let unique = match get_optional(json, "unique", Value::as_array)? {
+            Some(unique) => unique
+                .iter()
+                .map(|columns| {
+                    get_names(columns).ok_or(anyhow::anyhow!(
+                        "Invalid unique constraint {columns}"
+                    ))
+                })
+                .collect::<anyhow::Result<_>>()?,
+            None => vec![],
+        };
+        let checks = match get_optional(json, "checks", Value::as_array)? {
+            Some(checks) => checks
+                .iter()
+                .map(Expr::from_json)
+                .collect::<anyhow::Result<_>>()?,
+            None => vec![],
+        };
+
+        Ok(Self::new(
+            columns,
+            column_names,
+            get_optional(json, "primary_key", get_names)?,
+            unique,
+            checks,
+        ))
+    }
+}
+
+impl FromJson for SpreadsheetSchema {
+    fn from_json(json: &Value) -> anyhow::Result<Self> {
+        // older JSON has no named types
+        let types_json = get_optional(json, "types", Value::as_object)?;
+        let mut types = HashMap::new();
+        let mut type_names = Vec::new();
+        for (name, data_type) in types_json.into_iter().flatten() {
+            let name: Rc<str> = name.as_str().into();
+            types.insert(name.clone(), SharedDataType::from_json(data_type)?);
+            type_names.push(name);
+        }
+
+        let tables_json = get_required(json, "tables", Value::as_object)?;
+        let mut tables = HashMap::new();
+        let mut table_names = Vec::new();
+        for (name, table) in tables_json {
+            let name: Rc<str> = name.as_str().into();
+            let mut table = TableSchema::from_json(table)?;
+            // columns of a named type share it, as they do when parsed
+            for column in table.columns.values_mut() {
+                if let Some(alias) = &column.type_alias {
+                    column.column_type =
+                        types.get(alias).cloned().ok_or_else(|| {
+                            SchemaError::UnknownType {
+                                name: alias.to_string(),
+                            }
+                        })?;
+                }
+            }
+            tables.insert(name.clone(), Rc::new(table));
+            table_names.push(name);
+        }
+        Ok(Self::new(tables, table_names, types, type_names))
+    }
+}
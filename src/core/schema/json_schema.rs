@@ -0,0 +1,201 @@
+use crate::{
+    core::schema::{
+        BoolDataType, DateDataType, DateTimeDataType, DblDataType, DecDataType,
+        EnumDataType, IntDataType, LongDataType, SpreadsheetSchema,
+        StrDataType, TableSchema, TimeDataType,
+    },
+    json::{ToJson, ToJsonSchema},
+};
+use serde_json::{Map, Value, json};
+
+/// The dialect of JSON Schema that documents are generated for.
+pub const JSON_SCHEMA_DRAFT: &str =
+    "https://json-schema.org/draft/2020-12/schema";
+
+// Adds `null` to the allowed types of a nullable value.
+fn json_type(type_name: &str, nullable: bool) -> Value {
+    match nullable {
+        true => json!([type_name, "null"]),
+        false => json!(type_name),
+    }
+}
+
+// Builds a schema for the given JSON type, keeping only the keywords that
+// are set.
+fn typed_schema(
+    type_name: &str,
+    nullable: bool,
+    keywords: Vec<(&str, Option<Value>)>,
+) -> Value {
+    let mut schema = Map::new();
+    schema.insert("type".into(), json_type(type_name, nullable));
+    for (keyword, val) in keywords {
+        if let Some(val) = val {
+            schema.insert(keyword.into(), val);
+        }
+    }
+    Value::Object(schema)
+}
+
+impl ToJsonSchema for IntDataType {
+    fn to_json_schema(&self) -> Value {
+        typed_schema(
+            "integer",
+            self.nullable,
+            vec![
+                ("minimum", self.min.map(Value::from)),
+                ("maximum", self.max.map(Value::from)),
+            ],
+        )
+    }
+}
+
+impl ToJsonSchema for LongDataType {
+    fn to_json_schema(&self) -> Value {
+        typed_schema(
+            "integer",
+            self.nullable,
+            vec![
+                ("minimum", self.min.map(Value::from)),
+                ("maximum", self.max.map(Value::from)),
+            ],
+        )
+    }
+}
+
+impl ToJsonSchema for DblDataType {
+    fn to_json_schema(&self) -> Value {
+        typed_schema(
+            "number",
+            self.nullable,
+            vec![
+                ("minimum", self.min.map(Value::from)),
+                ("maximum", self.max.map(Value::from)),
+            ],
+        )
+    }
+}
+
+impl ToJsonSchema for DecDataType {
+    fn to_json_schema(&self) -> Value {
+        // decimals are written as strings, so their digits can be checked
+        typed_schema(
+            "string",
+            self.nullable,
+            vec![("pattern", Some(Value::from(self.get_digits_pattern())))],
+        )
+    }
+}
+
+impl ToJsonSchema for StrDataType {
+    fn to_json_schema(&self) -> Value {
+        let pattern = self.pattern.as_ref().map(|pattern| pattern.as_str());
+        typed_schema(
+            "string",
+            self.nullable,
+            vec![
+                ("minLength", self.min.map(Value::from)),
+                ("maxLength", self.max.map(Value::from)),
+                ("pattern", pattern.map(Value::from)),
+            ],
+        )
+    }
+}
+
+impl ToJsonSchema for BoolDataType {
+    fn to_json_schema(&self) -> Value {
+        typed_schema("boolean", self.nullable, vec![])
+    }
+}
+
+impl ToJsonSchema for DateDataType {
+    fn to_json_schema(&self) -> Value {
+        typed_schema(
+            "string",
+            self.nullable,
+            vec![("format", Some(json!("date")))],
+        )
+    }
+}
+
+// The `time` and `date-time` formats require a UTC offset, which these
+// values don't have, so patterns are used instead.
+
+impl ToJsonSchema for TimeDataType {
+    fn to_json_schema(&self) -> Value {
+        typed_schema(
+            "string",
+            self.nullable,
+            vec![("pattern", Some(json!(r"^\d{2}:\d{2}:\d{2}$")))],
+        )
+    }
+}
+
+impl ToJsonSchema for DateTimeDataType {
+    fn to_json_schema(&self) -> Value {
+        typed_schema(
+            "string",
+            self.nullable,
+            vec![(
+                "pattern",
+                Some(json!(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}$")),
+            )],
+        )
+    }
+}
+
+impl ToJsonSchema for EnumDataType {
+    fn to_json_schema(&self) -> Value {
+        let mut variants: Vec<Value> =
+            self.variants.iter().map(|val| json!(&**val)).collect();
+        if self.nullable {
+            variants.push(Value::Null);
+        }
+        json!({"enum": variants})
+    }
+}
+
+impl TableSchema {
+    /// Describes a row of the table, as a JSON object keyed by column name, in
+    /// a JSON Schema document. Columns that can't be null and have no default
+    /// are required.
+    pub fn to_json_schema(&self, title: &str) -> Value {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        for name in &self.column_names {
+            let column = &self.columns[name];
+            let mut schema = column.column_type.to_json_schema();
+            match &column.default_value {
+                Some(val) => schema["default"] = val.to_json(),
+                None if !column.column_type.get_nullable() => {
+                    required.push(name.to_string())
+                }
+                None => {}
+            }
+            properties.insert(name.to_string(), schema);
+        }
+
+        json!({
+            "$schema": JSON_SCHEMA_DRAFT,
+            "title": title,
+            "type": "object",
+            "properties": properties,
+            "required": required,
+            "additionalProperties": false
+        })
+    }
+}
+
+impl SpreadsheetSchema {
+    /// Generates a JSON Schema document for each table, keyed by table name.
+    pub fn to_json_schemas(&self) -> Value {
+        Value::Object(
+            self.table_names
+                .iter()
+                .map(|name| {
+                    (name.to_string(), self.tables[name].to_json_schema(name))
+                })
+                .collect(),
+        )
+    }
+}
@@ -1,9 +1,26 @@
+pub mod check;
 pub mod codegen;
 pub mod json;
+pub mod json_schema;
+pub mod rust;
+pub mod sql;
+pub mod ts;
+pub mod unique;
+pub mod violation;
 
-use anyhow::Ok;
-
-use crate::{json::ToJson, ql::lex::Literal};
+use crate::{
+    codegen::Codegen,
+    core::schema::check::{Expr, ExprType},
+    error::SchemaError,
+    json::ToJson,
+    ql::{
+        lex::Literal,
+        span::{Subject, SubjectError},
+    },
+};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use regex::Regex;
+use rust_decimal::Decimal;
 use std::{
     collections::HashMap,
     fmt::{Debug, Display},
@@ -11,29 +28,53 @@ use std::{
 };
 
 pub const INT_TYPE_NAME: &str = "int";
+pub const LONG_TYPE_NAME: &str = "long";
+pub const DEC_TYPE_NAME: &str = "dec";
 pub const DBL_TYPE_NAME: &str = "dbl";
 pub const STR_TYPE_NAME: &str = "str";
+pub const BOOL_TYPE_NAME: &str = "bool";
+pub const DATE_TYPE_NAME: &str = "date";
+pub const TIME_TYPE_NAME: &str = "time";
+pub const DATETIME_TYPE_NAME: &str = "datetime";
+pub const ENUM_TYPE_NAME: &str = "enum";
+
+/// The largest precision that `DecDataType` values can be stored with.
+pub const MAX_DEC_PRECISION: u32 = Decimal::MAX_SCALE;
 
 /// Represents a data type in the application.
-pub trait DataType: ToJson + Display {
+pub trait DataType: ToJson + Codegen + Display {
     fn get_nullable(&self) -> bool;
 
-    fn validate_literal(&self, lit: Option<&Literal>) -> anyhow::Result<()> {
+    /// Returns the name of the built-in type, e.g. `INT_TYPE_NAME`.
+    fn get_type_name(&self) -> &'static str;
+
+    fn validate_literal(
+        &self,
+        lit: Option<&Literal>,
+    ) -> Result<(), SchemaError> {
         match lit {
             Some(lit) => self.validator(lit),
             None => {
                 if self.get_nullable() {
                     return Ok(());
                 } else {
-                    return Err(anyhow::anyhow!("Required value was null!"));
+                    return Err(SchemaError::NullViolation);
                 }
             }
         }
     }
 
-    fn validate_data_type(&self) -> anyhow::Result<()>;
+    fn validate_data_type(&self) -> Result<(), SchemaError>;
 
-    fn validator(&self, lit: &Literal) -> anyhow::Result<()>;
+    fn validator(&self, lit: &Literal) -> Result<(), SchemaError>;
+}
+
+// Builds the error for a literal of the wrong type for `expected`.
+fn type_mismatch(expected: &str, lit: &Literal) -> SchemaError {
+    SchemaError::TypeMismatch {
+        expected: expected.into(),
+        found: lit.to_string(),
+    }
 }
 
 /// Type alias over `Rc<dyn DataType>` for convenience.
@@ -52,25 +93,23 @@ impl IntDataType {
         Self { min, max, nullable }
     }
 
-    fn validate_i32(&self, val: i32) -> anyhow::Result<()> {
+    fn validate_i32(&self, val: i32) -> Result<(), SchemaError> {
         if let Some(min) = self.min
             && val < min
         {
-            return Err(anyhow::anyhow!(
-                "Minimum value {} (entered {})",
-                min,
-                val
-            ));
+            return Err(SchemaError::BelowMin {
+                min: min.to_string(),
+                value: val.to_string(),
+            });
         }
 
         if let Some(max) = self.max
             && val > max
         {
-            return Err(anyhow::anyhow!(
-                "Maximum value {} (entered {})",
-                max,
-                val
-            ));
+            return Err(SchemaError::AboveMax {
+                max: max.to_string(),
+                value: val.to_string(),
+            });
         }
 
         Ok(())
@@ -82,23 +121,196 @@ impl DataType for IntDataType {
         self.nullable
     }
 
-    fn validator(&self, lit: &Literal) -> anyhow::Result<()> {
+    fn get_type_name(&self) -> &'static str {
+        INT_TYPE_NAME
+    }
+
+    fn validator(&self, lit: &Literal) -> Result<(), SchemaError> {
         match lit {
             Literal::Int(val) => self.validate_i32(*val),
-            _ => Err(anyhow::anyhow!(
-                "Couldn't validate non-integer literal against integer type."
-            )),
+            _ => Err(type_mismatch(self.get_type_name(), lit)),
         }
     }
 
-    fn validate_data_type(&self) -> anyhow::Result<()> {
+    fn validate_data_type(&self) -> Result<(), SchemaError> {
         if let Some(min) = self.min
             && let Some(max) = self.max
             && min > max
         {
-            return Err(anyhow::anyhow!(
-                "Can't have min ({min}) > max ({max})"
-            ));
+            return Err(SchemaError::InvalidBounds {
+                min: min.to_string(),
+                max: max.to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Represents a 64-bit integer data type in the application.
+#[derive(Debug)]
+pub struct LongDataType {
+    min: Option<i64>,
+    max: Option<i64>,
+    nullable: bool,
+}
+
+impl LongDataType {
+    pub fn new(min: Option<i64>, max: Option<i64>, nullable: bool) -> Self {
+        Self { min, max, nullable }
+    }
+
+    fn validate_i64(&self, val: i64) -> Result<(), SchemaError> {
+        if let Some(min) = self.min
+            && val < min
+        {
+            return Err(SchemaError::BelowMin {
+                min: min.to_string(),
+                value: val.to_string(),
+            });
+        }
+
+        if let Some(max) = self.max
+            && val > max
+        {
+            return Err(SchemaError::AboveMax {
+                max: max.to_string(),
+                value: val.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl DataType for LongDataType {
+    fn get_nullable(&self) -> bool {
+        self.nullable
+    }
+
+    fn get_type_name(&self) -> &'static str {
+        LONG_TYPE_NAME
+    }
+
+    fn validator(&self, lit: &Literal) -> Result<(), SchemaError> {
+        match lit.get_i64() {
+            Some(val) => self.validate_i64(val),
+            None => Err(type_mismatch(self.get_type_name(), lit)),
+        }
+    }
+
+    fn validate_data_type(&self) -> Result<(), SchemaError> {
+        if let Some(min) = self.min
+            && let Some(max) = self.max
+            && min > max
+        {
+            return Err(SchemaError::InvalidBounds {
+                min: min.to_string(),
+                max: max.to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Represents a fixed-scale decimal data type in the application, with at
+/// most `precision` significant digits, `scale` of which follow the decimal
+/// point.
+#[derive(Debug)]
+pub struct DecDataType {
+    precision: u32,
+    scale: u32,
+    nullable: bool,
+}
+
+impl DecDataType {
+    pub fn new(precision: u32, scale: u32, nullable: bool) -> Self {
+        Self {
+            precision,
+            scale,
+            nullable,
+        }
+    }
+
+    /// The number of digits allowed before the decimal point. It's zero for
+    /// a type with scale > precision, which `validate_data_type` rejects.
+    pub fn get_int_digits(&self) -> u32 {
+        self.precision.saturating_sub(self.scale)
+    }
+
+    /// A regex matching the text of the values this type accepts, as
+    /// `Literal::to_json` writes them. Trailing zeros after the decimal
+    /// point don't count towards the scale.
+    pub fn get_digits_pattern(&self) -> String {
+        let int_part = match self.get_int_digits() {
+            0 => "0".to_string(),
+            digits => format!("[0-9]{{1,{digits}}}"),
+        };
+        let fraction = match self.scale {
+            0 => r"(\.0+)?".to_string(),
+            scale => format!(r"(\.[0-9]{{1,{scale}}}0*)?"),
+        };
+        format!("^-?{int_part}{fraction}$")
+    }
+
+    fn validate_dec(&self, val: Decimal) -> Result<(), SchemaError> {
+        let val = val.normalize();
+
+        if val.scale() > self.scale {
+            return Err(SchemaError::TooManyDecimals {
+                scale: self.scale,
+                value: val.to_string(),
+            });
+        }
+
+        let int_part = val.trunc().abs();
+        let int_digits = if int_part.is_zero() {
+            0
+        } else {
+            int_part.to_string().len() as u32
+        };
+        if int_digits > self.get_int_digits() {
+            return Err(SchemaError::TooManyDigits {
+                digits: self.get_int_digits(),
+                value: val.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl DataType for DecDataType {
+    fn get_nullable(&self) -> bool {
+        self.nullable
+    }
+
+    fn get_type_name(&self) -> &'static str {
+        DEC_TYPE_NAME
+    }
+
+    fn validator(&self, lit: &Literal) -> Result<(), SchemaError> {
+        match lit.get_dec() {
+            Some(val) => self.validate_dec(val),
+            None => Err(type_mismatch(self.get_type_name(), lit)),
+        }
+    }
+
+    fn validate_data_type(&self) -> Result<(), SchemaError> {
+        if self.precision == 0 || self.precision > MAX_DEC_PRECISION {
+            return Err(SchemaError::InvalidType {
+                message: format!(
+                    "Precision must be between 1 and {} (got {})",
+                    MAX_DEC_PRECISION, self.precision
+                ),
+            });
+        }
+        if self.scale > self.precision {
+            return Err(SchemaError::InvalidType {
+                message: format!(
+                    "Can't have scale ({}) > precision ({})",
+                    self.scale, self.precision
+                ),
+            });
         }
         Ok(())
     }
@@ -118,25 +330,23 @@ impl DblDataType {
         Self { min, max, nullable }
     }
 
-    fn validate_f64(&self, val: f64) -> anyhow::Result<()> {
+    fn validate_f64(&self, val: f64) -> Result<(), SchemaError> {
         if let Some(min) = self.min
             && val < min
         {
-            return Err(anyhow::anyhow!(
-                "Minimum value {} (entered {})",
-                min,
-                val
-            ));
+            return Err(SchemaError::BelowMin {
+                min: min.to_string(),
+                value: val.to_string(),
+            });
         }
 
         if let Some(max) = self.max
             && val > max
         {
-            return Err(anyhow::anyhow!(
-                "Maximum value {} (entered {})",
-                max,
-                val
-            ));
+            return Err(SchemaError::AboveMax {
+                max: max.to_string(),
+                value: val.to_string(),
+            });
         }
 
         Ok(())
@@ -148,51 +358,76 @@ impl DataType for DblDataType {
         self.nullable
     }
 
-    fn validator(&self, lit: &Literal) -> anyhow::Result<()> {
-        match lit {
-            Literal::Dbl(val) => self.validate_f64(*val),
-            _ => Err(anyhow::anyhow!(
-                "Couldn't validate non-double literal against double type."
-            )),
+    fn get_type_name(&self) -> &'static str {
+        DBL_TYPE_NAME
+    }
+
+    fn validator(&self, lit: &Literal) -> Result<(), SchemaError> {
+        match lit.get_f64() {
+            Some(val) => self.validate_f64(val),
+            None => Err(type_mismatch(self.get_type_name(), lit)),
         }
     }
 
-    fn validate_data_type(&self) -> anyhow::Result<()> {
+    fn validate_data_type(&self) -> Result<(), SchemaError> {
         if let Some(min) = self.min
             && let Some(max) = self.max
             && min > max
         {
-            return Err(anyhow::anyhow!(
-                "Can't have min ({min}) > max ({max})"
-            ));
+            return Err(SchemaError::InvalidBounds {
+                min: min.to_string(),
+                max: max.to_string(),
+            });
         }
         Ok(())
     }
 }
 
-/// Represents a string data type in the application.
+/// Represents a string data type in the application. Lengths are counted in
+/// characters (Unicode code points), as JSON Schema and SQL count them.
 #[derive(Debug)]
 pub struct StrDataType {
     min: Option<usize>,
     max: Option<usize>,
+    // compiled once when the type is defined
+    pattern: Option<Regex>,
     nullable: bool,
 }
 
 impl StrDataType {
-    pub fn new(min: Option<usize>, max: Option<usize>, nullable: bool) -> Self {
-        Self { min, max, nullable }
+    pub fn new(
+        min: Option<usize>,
+        max: Option<usize>,
+        pattern: Option<Regex>,
+        nullable: bool,
+    ) -> Self {
+        Self {
+            min,
+            max,
+            pattern,
+            nullable,
+        }
     }
 
-    fn validate_str(&self, s: &str) -> anyhow::Result<()> {
+    fn validate_str(&self, s: &str) -> Result<(), SchemaError> {
+        let len = s.chars().count();
         if let Some(min) = &self.min
-            && s.len() < *min
+            && len < *min
         {
-            return Err(anyhow::anyhow!("Minimum length {min}"));
+            return Err(SchemaError::TooShort { min: *min, len });
         }
         if let Some(max) = &self.max
-            && s.len() < *max
+            && len > *max
+        {
+            return Err(SchemaError::TooLong { max: *max, len });
+        }
+        if let Some(pattern) = &self.pattern
+            && !pattern.is_match(s)
         {
-            return Err(anyhow::anyhow!("Minimum length {max}"));
+            return Err(SchemaError::PatternMismatch {
+                pattern: pattern.to_string(),
+                value: s.into(),
+            });
         }
         Ok(())
     }
@@ -203,42 +438,385 @@ impl DataType for StrDataType {
         self.nullable
     }
 
-    fn validator(&self, lit: &Literal) -> anyhow::Result<()> {
+    fn get_type_name(&self) -> &'static str {
+        STR_TYPE_NAME
+    }
+
+    fn validator(&self, lit: &Literal) -> Result<(), SchemaError> {
         match lit {
             Literal::Str(val) => self.validate_str(&val),
-            _ => Err(anyhow::anyhow!(
-                "Couldn't validate non-string literal against string type."
-            )),
+            _ => Err(type_mismatch(self.get_type_name(), lit)),
         }
     }
 
-    fn validate_data_type(&self) -> anyhow::Result<()> {
+    fn validate_data_type(&self) -> Result<(), SchemaError> {
         if let Some(min) = self.min
             && let Some(max) = self.max
             && min > max
         {
-            return Err(anyhow::anyhow!(
-                "Can't have min ({min}) > max ({max})"
-            ));
+            return Err(SchemaError::InvalidBounds {
+                min: min.to_string(),
+                max: max.to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Represents a boolean data type in the application.
+#[derive(Debug)]
+pub struct BoolDataType {
+    nullable: bool,
+}
+
+impl BoolDataType {
+    pub fn new(nullable: bool) -> Self {
+        Self { nullable }
+    }
+}
+
+impl DataType for BoolDataType {
+    fn get_nullable(&self) -> bool {
+        self.nullable
+    }
+
+    fn get_type_name(&self) -> &'static str {
+        BOOL_TYPE_NAME
+    }
+
+    fn validator(&self, lit: &Literal) -> Result<(), SchemaError> {
+        match lit {
+            Literal::Bool(_) => Ok(()),
+            _ => Err(type_mismatch(self.get_type_name(), lit)),
+        }
+    }
+
+    fn validate_data_type(&self) -> Result<(), SchemaError> {
+        Ok(())
+    }
+}
+
+/// Represents a date data type in the application.
+#[derive(Debug)]
+pub struct DateDataType {
+    min: Option<NaiveDate>,
+    max: Option<NaiveDate>,
+    nullable: bool,
+}
+
+impl DateDataType {
+    pub fn new(
+        min: Option<NaiveDate>,
+        max: Option<NaiveDate>,
+        nullable: bool,
+    ) -> Self {
+        Self { min, max, nullable }
+    }
+
+    fn validate_date(&self, val: NaiveDate) -> Result<(), SchemaError> {
+        if let Some(min) = self.min
+            && val < min
+        {
+            return Err(SchemaError::BelowMin {
+                min: min.to_string(),
+                value: val.to_string(),
+            });
+        }
+
+        if let Some(max) = self.max
+            && val > max
+        {
+            return Err(SchemaError::AboveMax {
+                max: max.to_string(),
+                value: val.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl DataType for DateDataType {
+    fn get_nullable(&self) -> bool {
+        self.nullable
+    }
+
+    fn get_type_name(&self) -> &'static str {
+        DATE_TYPE_NAME
+    }
+
+    fn validator(&self, lit: &Literal) -> Result<(), SchemaError> {
+        match lit.get_date() {
+            Some(val) => self.validate_date(val),
+            None => Err(type_mismatch(self.get_type_name(), lit)),
+        }
+    }
+
+    fn validate_data_type(&self) -> Result<(), SchemaError> {
+        if let Some(min) = self.min
+            && let Some(max) = self.max
+            && min > max
+        {
+            return Err(SchemaError::InvalidBounds {
+                min: min.to_string(),
+                max: max.to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Represents a time data type in the application.
+#[derive(Debug)]
+pub struct TimeDataType {
+    min: Option<NaiveTime>,
+    max: Option<NaiveTime>,
+    nullable: bool,
+}
+
+impl TimeDataType {
+    pub fn new(
+        min: Option<NaiveTime>,
+        max: Option<NaiveTime>,
+        nullable: bool,
+    ) -> Self {
+        Self { min, max, nullable }
+    }
+
+    fn validate_time(&self, val: NaiveTime) -> Result<(), SchemaError> {
+        if let Some(min) = self.min
+            && val < min
+        {
+            return Err(SchemaError::BelowMin {
+                min: min.to_string(),
+                value: val.to_string(),
+            });
+        }
+
+        if let Some(max) = self.max
+            && val > max
+        {
+            return Err(SchemaError::AboveMax {
+                max: max.to_string(),
+                value: val.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl DataType for TimeDataType {
+    fn get_nullable(&self) -> bool {
+        self.nullable
+    }
+
+    fn get_type_name(&self) -> &'static str {
+        TIME_TYPE_NAME
+    }
+
+    fn validator(&self, lit: &Literal) -> Result<(), SchemaError> {
+        match lit.get_time() {
+            Some(val) => self.validate_time(val),
+            None => Err(type_mismatch(self.get_type_name(), lit)),
+        }
+    }
+
+    fn validate_data_type(&self) -> Result<(), SchemaError> {
+        if let Some(min) = self.min
+            && let Some(max) = self.max
+            && min > max
+        {
+            return Err(SchemaError::InvalidBounds {
+                min: min.to_string(),
+                max: max.to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Represents a datetime data type in the application.
+#[derive(Debug)]
+pub struct DateTimeDataType {
+    min: Option<NaiveDateTime>,
+    max: Option<NaiveDateTime>,
+    nullable: bool,
+}
+
+impl DateTimeDataType {
+    pub fn new(
+        min: Option<NaiveDateTime>,
+        max: Option<NaiveDateTime>,
+        nullable: bool,
+    ) -> Self {
+        Self { min, max, nullable }
+    }
+
+    fn validate_datetime(&self, val: NaiveDateTime) -> Result<(), SchemaError> {
+        if let Some(min) = self.min
+            && val < min
+        {
+            return Err(SchemaError::BelowMin {
+                min: min.to_string(),
+                value: val.to_string(),
+            });
+        }
+
+        if let Some(max) = self.max
+            && val > max
+        {
+            return Err(SchemaError::AboveMax {
+                max: max.to_string(),
+                value: val.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl DataType for DateTimeDataType {
+    fn get_nullable(&self) -> bool {
+        self.nullable
+    }
+
+    fn get_type_name(&self) -> &'static str {
+        DATETIME_TYPE_NAME
+    }
+
+    fn validator(&self, lit: &Literal) -> Result<(), SchemaError> {
+        match lit.get_datetime() {
+            Some(val) => self.validate_datetime(val),
+            None => Err(type_mismatch(self.get_type_name(), lit)),
+        }
+    }
+
+    fn validate_data_type(&self) -> Result<(), SchemaError> {
+        if let Some(min) = self.min
+            && let Some(max) = self.max
+            && min > max
+        {
+            return Err(SchemaError::InvalidBounds {
+                min: min.to_string(),
+                max: max.to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Represents an enumerated string data type in the application, whose values
+/// are restricted to a fixed set of variants.
+#[derive(Debug)]
+pub struct EnumDataType {
+    variants: Vec<Rc<str>>,
+    nullable: bool,
+}
+
+impl EnumDataType {
+    pub fn new(variants: Vec<Rc<str>>, nullable: bool) -> Self {
+        Self { variants, nullable }
+    }
+
+    pub fn get_variants(&self) -> &[Rc<str>] {
+        &self.variants
+    }
+}
+
+impl DataType for EnumDataType {
+    fn get_nullable(&self) -> bool {
+        self.nullable
+    }
+
+    fn get_type_name(&self) -> &'static str {
+        ENUM_TYPE_NAME
+    }
+
+    fn validator(&self, lit: &Literal) -> Result<(), SchemaError> {
+        match lit {
+            Literal::Str(val) => {
+                if self.variants.contains(val) {
+                    Ok(())
+                } else {
+                    Err(SchemaError::UnknownVariant {
+                        value: val.to_string(),
+                    })
+                }
+            }
+            _ => Err(type_mismatch(self.get_type_name(), lit)),
+        }
+    }
+
+    fn validate_data_type(&self) -> Result<(), SchemaError> {
+        if self.variants.is_empty() {
+            return Err(SchemaError::InvalidType {
+                message: "Enum must have at least one variant".into(),
+            });
+        }
+        for (i, variant) in self.variants.iter().enumerate() {
+            if self.variants[..i].contains(variant) {
+                return Err(SchemaError::InvalidType {
+                    message: format!(
+                        "Can't have multiple enum variants named '{variant}'"
+                    ),
+                });
+            }
         }
         Ok(())
     }
 }
 
+/// Represents a reference from a column to a column in another table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ForeignKey {
+    table: Rc<str>,
+    column: Rc<str>,
+}
+
+impl ForeignKey {
+    pub fn new(table: Rc<str>, column: Rc<str>) -> Self {
+        Self { table, column }
+    }
+
+    pub fn get_table(&self) -> &str {
+        &self.table
+    }
+
+    pub fn get_column(&self) -> &str {
+        &self.column
+    }
+}
+
+/// Returns whether values of type `a` can be compared with values of type
+/// `b`, e.g. for a foreign key.
+pub fn types_compatible(a: &dyn DataType, b: &dyn DataType) -> bool {
+    let is_integer = |name| name == INT_TYPE_NAME || name == LONG_TYPE_NAME;
+    a.get_type_name() == b.get_type_name()
+        || (is_integer(a.get_type_name()) && is_integer(b.get_type_name()))
+}
+
 /// Represents a column schema in the application.
 pub struct ColumnSchema {
     column_type: Rc<dyn DataType>,
+    // the name of the `type` declaration the column's type came from, if any
+    type_alias: Option<Rc<str>>,
     default_value: Option<Literal>,
+    references: Option<ForeignKey>,
 }
 
 impl ColumnSchema {
     pub fn new(
         column_type: Rc<dyn DataType>,
+        type_alias: Option<Rc<str>>,
         default_value: Option<Literal>,
+        references: Option<ForeignKey>,
     ) -> Self {
         Self {
             column_type,
+            type_alias,
             default_value,
+            references,
         }
     }
 
@@ -246,9 +824,39 @@ impl ColumnSchema {
         self.column_type.clone()
     }
 
-    pub fn validate_column_schema(&self) -> anyhow::Result<()> {
-        self.get_type().validate_data_type()
+    /// The name of the `type` declaration the column's type came from, if it
+    /// wasn't written out in full.
+    pub fn get_type_alias(&self) -> Option<&str> {
+        self.type_alias.as_deref()
+    }
+
+    pub fn get_default_value(&self) -> Option<&Literal> {
+        self.default_value.as_ref()
     }
+
+    pub fn get_references(&self) -> Option<&ForeignKey> {
+        self.references.as_ref()
+    }
+
+    pub fn validate_column_schema(&self) -> Result<(), SchemaError> {
+        let column_type = self.get_type();
+        column_type.validate_data_type()?;
+        // the default must be a valid value of the column's own type
+        if let Some(default) = &self.default_value {
+            column_type.validate_literal(Some(default)).map_err(|e| {
+                SchemaError::InvalidDefault {
+                    value: default.to_string(),
+                    error: Box::new(e),
+                }
+            })?;
+        }
+        Ok(())
+    }
+}
+
+// Builds the error for a table constraint that doesn't fit its table.
+fn invalid_constraint(message: String) -> SchemaError {
+    SchemaError::InvalidConstraint { message }
 }
 
 /// Represents a table schema in the application.
@@ -256,16 +864,28 @@ pub struct TableSchema {
     columns: HashMap<Rc<str>, ColumnSchema>,
     // keeps track of the order in which columns are defined
     column_names: Vec<Rc<str>>,
+    // names of the columns that identify a row, if declared
+    primary_key: Option<Vec<Rc<str>>>,
+    // groups of columns whose values must be unique across rows
+    unique: Vec<Vec<Rc<str>>>,
+    // boolean expressions that every row must satisfy
+    checks: Vec<Expr>,
 }
 
 impl TableSchema {
     pub fn new(
         columns: HashMap<Rc<str>, ColumnSchema>,
         column_names: Vec<Rc<str>>,
+        primary_key: Option<Vec<Rc<str>>>,
+        unique: Vec<Vec<Rc<str>>>,
+        checks: Vec<Expr>,
     ) -> Self {
         Self {
             columns,
             column_names,
+            primary_key,
+            unique,
+            checks,
         }
     }
 
@@ -277,10 +897,102 @@ impl TableSchema {
         self.columns.get(column_name)
     }
 
-    pub fn validate_table_schema(&self) -> anyhow::Result<()> {
+    /// Returns the column names in the order they were defined.
+    pub fn get_column_names(&self) -> &[Rc<str>] {
+        &self.column_names
+    }
+
+    pub fn get_primary_key(&self) -> Option<&[Rc<str>]> {
+        self.primary_key.as_deref()
+    }
+
+    pub fn get_unique_constraints(&self) -> &[Vec<Rc<str>>] {
+        &self.unique
+    }
+
+    pub fn get_checks(&self) -> &[Expr] {
+        &self.checks
+    }
+
+    /// Returns the position of a column in `get_column_names`.
+    pub fn get_column_index(&self, column_name: &str) -> Option<usize> {
+        self.column_names
+            .iter()
+            .position(|name| &**name == column_name)
+    }
+
+    fn validate_unique_constraint(
+        &self,
+        columns: &[Rc<str>],
+    ) -> Result<(), SchemaError> {
+        if columns.is_empty() {
+            return Err(invalid_constraint(
+                "Unique constraint must have at least one column".into(),
+            ));
+        }
+        for (i, column_name) in columns.iter().enumerate() {
+            if columns[..i].contains(column_name) {
+                return Err(invalid_constraint(format!(
+                    "Unique column '{column_name}' is listed more than once"
+                )));
+            }
+            if !self.columns.contains_key(column_name) {
+                return Err(invalid_constraint(format!(
+                    "Unique column '{column_name}' doesn't exist"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_primary_key(&self, key: &[Rc<str>]) -> Result<(), SchemaError> {
+        if key.is_empty() {
+            return Err(invalid_constraint(
+                "Primary key must have at least one column".into(),
+            ));
+        }
+        for (i, column_name) in key.iter().enumerate() {
+            if key[..i].contains(column_name) {
+                return Err(invalid_constraint(format!(
+                    "Primary key column '{column_name}' is listed more than once"
+                )));
+            }
+            match self.columns.get(column_name) {
+                Some(column) if column.get_type().get_nullable() => {
+                    return Err(invalid_constraint(format!(
+                        "Primary key column '{column_name}' can't be nullable"
+                    )));
+                }
+                Some(_) => {}
+                None => {
+                    return Err(invalid_constraint(format!(
+                        "Primary key column '{column_name}' doesn't exist"
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn validate_table_schema(&self) -> Result<(), SchemaError> {
         for (_col_name, col) in &self.columns {
             col.validate_column_schema()?;
         }
+        self.validate_table_constraints()
+    }
+
+    /// Validates the primary key, unique constraints and checks, leaving out
+    /// the columns themselves.
+    fn validate_table_constraints(&self) -> Result<(), SchemaError> {
+        if let Some(key) = &self.primary_key {
+            self.validate_primary_key(key)?;
+        }
+        for columns in &self.unique {
+            self.validate_unique_constraint(columns)?;
+        }
+        for check in &self.checks {
+            check.type_check(self)?.expect(ExprType::Bool, check)?;
+        }
         Ok(())
     }
 }
@@ -291,16 +1003,24 @@ pub type SharedTableSchema = Rc<TableSchema>;
 pub struct SpreadsheetSchema {
     tables: HashMap<Rc<str>, SharedTableSchema>,
     table_names: Vec<Rc<str>>,
+    // named types from `type` declarations
+    types: HashMap<Rc<str>, SharedDataType>,
+    // keeps track of the order in which types are declared
+    type_names: Vec<Rc<str>>,
 }
 
 impl SpreadsheetSchema {
     pub fn new(
         tables: HashMap<Rc<str>, SharedTableSchema>,
         table_names: Vec<Rc<str>>,
+        types: HashMap<Rc<str>, SharedDataType>,
+        type_names: Vec<Rc<str>>,
     ) -> Self {
         Self {
             tables,
             table_names,
+            types,
+            type_names,
         }
     }
 
@@ -312,10 +1032,135 @@ impl SpreadsheetSchema {
         self.tables.get(name).map(|ptr| ptr.clone())
     }
 
-    pub fn validate_spreadsheet_schema(&self) -> anyhow::Result<()> {
-        for (_table_name, table) in &self.tables {
-            table.validate_table_schema()?;
+    pub fn get_table_names(&self) -> &[Rc<str>] {
+        &self.table_names
+    }
+
+    pub fn get_type(&self, name: &str) -> Option<SharedDataType> {
+        self.types.get(name).cloned()
+    }
+
+    pub fn get_type_names(&self) -> &[Rc<str>] {
+        &self.type_names
+    }
+
+    /// The named types that no column uses, in declaration order.
+    pub fn find_unused_types(&self) -> Vec<Rc<str>> {
+        self.type_names
+            .iter()
+            .filter(|name| {
+                let data_type = &self.types[*name];
+                !self.tables.values().any(|table| {
+                    table
+                        .columns
+                        .values()
+                        .any(|col| Rc::ptr_eq(&col.column_type, data_type))
+                })
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Checks that the target of a foreign key exists and has a compatible
+    /// type.
+    fn validate_foreign_key(
+        &self,
+        table_name: &str,
+        column_name: &str,
+        column: &ColumnSchema,
+        fk: &ForeignKey,
+    ) -> Result<(), SchemaError> {
+        let column_name = format!("{table_name}.{column_name}");
+        let target_name = format!("{}.{}", fk.get_table(), fk.get_column());
+        let target_table =
+            self.tables.get(fk.get_table()).ok_or_else(|| {
+                SchemaError::UnknownTable {
+                    column: column_name.clone(),
+                    table: fk.get_table().into(),
+                }
+            })?;
+        let target_column = target_table
+            .get_column(fk.get_column())
+            .ok_or_else(|| SchemaError::UnknownColumn {
+                column: column_name.clone(),
+                target: target_name.clone(),
+            })?;
+        if !types_compatible(&*column.get_type(), &*target_column.get_type()) {
+            return Err(SchemaError::IncompatibleReference {
+                column: column_name,
+                column_type: column.get_type().get_type_name().into(),
+                target: target_name,
+                target_type: target_column.get_type().get_type_name().into(),
+            });
         }
         Ok(())
     }
+
+    /// Validates the whole schema, failing with the first error found and
+    /// the part of the schema it's about.
+    pub fn validate_spreadsheet_schema(&self) -> Result<(), SubjectError> {
+        match self.find_schema_errors().into_iter().next() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// Validates the whole schema, returning every error found rather than
+    /// stopping at the first. Types and tables are checked in the order
+    /// they're declared in.
+    pub fn find_schema_errors(&self) -> Vec<SubjectError> {
+        let mut errors = Vec::new();
+        // errors are tagged with what they're about, so they can be traced
+        // back to the source
+        let mut check = |result: Result<(), SchemaError>,
+                         subject: Subject,
+                         prefix: String| {
+            if let Err(e) = result {
+                let message = format!("{prefix}{e}");
+                errors.push(SubjectError::new(subject, message, e));
+            }
+        };
+
+        for type_name in &self.type_names {
+            check(
+                self.types[type_name].validate_data_type(),
+                Subject::Type(type_name.to_string()),
+                format!("Type '{type_name}': "),
+            );
+        }
+        for table_name in &self.table_names {
+            let table = &self.tables[table_name];
+            let column_subject = |column_name: &str| {
+                Subject::Column(table_name.to_string(), column_name.to_string())
+            };
+            for column_name in table.get_column_names() {
+                check(
+                    table.columns[column_name].validate_column_schema(),
+                    column_subject(column_name),
+                    format!("Column '{table_name}.{column_name}': "),
+                );
+            }
+            check(
+                table.validate_table_constraints(),
+                Subject::Table(table_name.to_string()),
+                String::new(),
+            );
+            for column_name in table.get_column_names() {
+                let column = &table.columns[column_name];
+                if let Some(fk) = column.get_references() {
+                    check(
+                        self.validate_foreign_key(
+                            table_name,
+                            column_name,
+                            column,
+                            fk,
+                        ),
+                        column_subject(column_name),
+                        String::new(),
+                    );
+                }
+            }
+        }
+        errors
+    }
 }
@@ -0,0 +1,253 @@
+use std::{fs, path::Path};
+
+use crate::{
+    core::schema::{
+        BoolDataType, DateDataType, DateTimeDataType, DblDataType, DecDataType,
+        EnumDataType, IntDataType, LongDataType, SpreadsheetSchema,
+        StrDataType, TableSchema, TimeDataType,
+    },
+    json::ToJson,
+    rust::{ToRust, rust_idents},
+};
+
+// Generated code refers to this crate by its absolute path, so it only needs
+// to depend on this crate.
+const LIT: &str = "::ftables_lib::ql::lex::Literal";
+
+// Types from the prelude are referred to by their full paths too, in case a
+// table's struct has the same name.
+const OPTION: &str = "::std::option::Option";
+const STRING: &str = "::std::string::String";
+
+// Wraps a non-nullable type in an `Option` if the value can be null.
+fn optional_type(rust_type: &str, nullable: bool) -> String {
+    match nullable {
+        true => format!("{OPTION}<{rust_type}>"),
+        false => rust_type.to_string(),
+    }
+}
+
+// Converts a `Copy` value to a literal with the given variant.
+fn copy_literal(variant: &str, field: &str, nullable: bool) -> String {
+    match nullable {
+        true => format!("{field}.map({LIT}::{variant})"),
+        false => format!("Some({LIT}::{variant}({field}))"),
+    }
+}
+
+// Converts a `String` value to a string literal.
+fn str_literal(field: &str, nullable: bool) -> String {
+    match nullable {
+        true => format!("{field}.as_deref().map(|val| {LIT}::Str(val.into()))"),
+        false => format!("Some({LIT}::Str({field}.as_str().into()))"),
+    }
+}
+
+impl ToRust for IntDataType {
+    fn rust_type(&self) -> String {
+        optional_type("i32", self.nullable)
+    }
+
+    fn rust_literal(&self, field: &str) -> String {
+        copy_literal("Int", field, self.nullable)
+    }
+}
+
+impl ToRust for LongDataType {
+    fn rust_type(&self) -> String {
+        optional_type("i64", self.nullable)
+    }
+
+    fn rust_literal(&self, field: &str) -> String {
+        copy_literal("Long", field, self.nullable)
+    }
+}
+
+impl ToRust for DblDataType {
+    fn rust_type(&self) -> String {
+        optional_type("f64", self.nullable)
+    }
+
+    fn rust_literal(&self, field: &str) -> String {
+        copy_literal("Dbl", field, self.nullable)
+    }
+}
+
+impl ToRust for DecDataType {
+    fn rust_type(&self) -> String {
+        optional_type("::ftables_lib::rust_decimal::Decimal", self.nullable)
+    }
+
+    fn rust_literal(&self, field: &str) -> String {
+        copy_literal("Dec", field, self.nullable)
+    }
+}
+
+impl ToRust for StrDataType {
+    fn rust_type(&self) -> String {
+        optional_type(STRING, self.nullable)
+    }
+
+    fn rust_literal(&self, field: &str) -> String {
+        str_literal(field, self.nullable)
+    }
+}
+
+impl ToRust for BoolDataType {
+    fn rust_type(&self) -> String {
+        optional_type("bool", self.nullable)
+    }
+
+    fn rust_literal(&self, field: &str) -> String {
+        copy_literal("Bool", field, self.nullable)
+    }
+}
+
+impl ToRust for DateDataType {
+    fn rust_type(&self) -> String {
+        optional_type("::ftables_lib::chrono::NaiveDate", self.nullable)
+    }
+
+    fn rust_literal(&self, field: &str) -> String {
+        copy_literal("Date", field, self.nullable)
+    }
+}
+
+impl ToRust for TimeDataType {
+    fn rust_type(&self) -> String {
+        optional_type("::ftables_lib::chrono::NaiveTime", self.nullable)
+    }
+
+    fn rust_literal(&self, field: &str) -> String {
+        copy_literal("Time", field, self.nullable)
+    }
+}
+
+impl ToRust for DateTimeDataType {
+    fn rust_type(&self) -> String {
+        optional_type("::ftables_lib::chrono::NaiveDateTime", self.nullable)
+    }
+
+    fn rust_literal(&self, field: &str) -> String {
+        copy_literal("DateTime", field, self.nullable)
+    }
+}
+
+impl ToRust for EnumDataType {
+    // variants aren't necessarily valid identifiers, so they're kept as
+    // strings and checked by `validate`
+    fn rust_type(&self) -> String {
+        optional_type(STRING, self.nullable)
+    }
+
+    fn rust_literal(&self, field: &str) -> String {
+        str_literal(field, self.nullable)
+    }
+}
+
+impl TableSchema {
+    /// Formats a row of the table `name` as a Rust struct called `ident`,
+    /// with serde derives and a `validate` method that checks it against the
+    /// table in the `FTABLES_SCHEMA` the generated module holds. Fields that
+    /// can't be named after their column are renamed for serde.
+    pub fn to_rust(&self, name: &str, ident: &str) -> String {
+        let mut fields = String::new();
+        let mut values = String::new();
+        let idents = rust_idents(&self.column_names);
+        for (col, ident) in self.column_names.iter().zip(&idents) {
+            let data_type = &self.columns[col].column_type;
+            if ident.trim_start_matches("r#") != &**col {
+                fields.push_str(&format!("    #[serde(rename = {col:?})]\n"));
+            }
+            fields.push_str(&format!(
+                "    pub {ident}: {},\n",
+                data_type.rust_type()
+            ));
+            let field = format!("self.{ident}");
+            values.push_str(&format!(
+                "            {},\n",
+                data_type.rust_literal(&field)
+            ));
+        }
+
+        format!(
+            "#[derive(Clone, Debug, PartialEq, ::ftables_lib::serde::Serialize, ::ftables_lib::serde::Deserialize)]
+#[serde(crate = \"::ftables_lib::serde\")]
+pub struct {ident} {{
+{fields}}}
+
+impl {ident} {{
+    /// Checks the row against the constraints on each column of `{name}`.
+    pub fn validate(&self) -> ::std::vec::Vec<::ftables_lib::core::schema::violation::CellViolation> {{
+        let row = [
+{values}        ];
+        FTABLES_SCHEMA.with(|schema| schema.get_table({name:?}).unwrap().validate_row(&row))
+    }}
+}}"
+        )
+    }
+}
+
+impl SpreadsheetSchema {
+    /// Generates Rust source for a struct per table, to be `include!`d by the
+    /// crate using it. The schema itself is embedded in its JSON form, so
+    /// that rows can be validated at runtime.
+    pub fn to_rust(&self) -> String {
+        let mut items = vec![format!(
+            "// Generated by ftables_lib. Do not edit.
+
+thread_local! {{
+    static FTABLES_SCHEMA: ::ftables_lib::core::schema::SpreadsheetSchema =
+        ::ftables_lib::parse_valid_schema_from_json(
+            &::ftables_lib::serde_json::from_str({:?}).unwrap(),
+        )
+        .unwrap();
+}}",
+            self.to_json().to_string()
+        )];
+        let idents = rust_idents(&self.table_names);
+        items.extend(
+            self.table_names
+                .iter()
+                .zip(&idents)
+                .map(|(name, ident)| self.tables[name].to_rust(name, ident)),
+        );
+        items.join("\n\n") + "\n"
+    }
+}
+
+/// Generates Rust structs for the schema in the file at `input`, writing
+/// them to `output`. Meant to be called from a build script, with `output`
+/// in `OUT_DIR`:
+///
+/// ```ignore
+/// ftables_lib::core::schema::rust::generate_structs(
+///     "schema.ft",
+///     Path::new(&env::var("OUT_DIR")?).join("schema.rs"),
+/// )?;
+/// ```
+///
+/// and the result brought in with
+/// `include!(concat!(env!("OUT_DIR"), "/schema.rs"));`.
+pub fn generate_structs(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+) -> anyhow::Result<()> {
+    let input = input.as_ref();
+    println!("cargo:rerun-if-changed={}", input.display());
+    let schema =
+        crate::parse_valid_schema_from_str(&fs::read_to_string(input)?)?;
+
+    // the generated code loads the schema back from JSON when validating,
+    // so any problem with that is reported now rather than at runtime
+    let json = schema.to_json();
+    if crate::parse_valid_schema_from_json(&json)?.to_json() != json {
+        return Err(anyhow::anyhow!(
+            "Schema in {} isn't read back from JSON unchanged",
+            input.display()
+        ));
+    }
+
+    fs::write(output, schema.to_rust())?;
+    Ok(())
+}
@@ -0,0 +1,367 @@
+use std::rc::Rc;
+
+use crate::{
+    core::schema::{
+        BoolDataType, ColumnSchema, DateDataType, DateTimeDataType,
+        DblDataType, DecDataType, EnumDataType, IntDataType, LongDataType,
+        SpreadsheetSchema, StrDataType, TableSchema, TimeDataType,
+        check::{BinaryOp, Expr, UnaryOp},
+    },
+    ql::lex::Literal,
+    sql::{SqlDialect, ToSql, quote_ident, quote_str},
+};
+
+/// Formats a literal as a SQL value.
+pub fn sql_literal(lit: &Literal, dialect: SqlDialect) -> String {
+    match (lit, dialect) {
+        (Literal::Str(val), _) => quote_str(val),
+        // SQLite stores booleans as integers
+        (Literal::Bool(val), SqlDialect::Sqlite) => (*val as i32).to_string(),
+        (Literal::Bool(val), SqlDialect::Postgres) => {
+            val.to_string().to_uppercase()
+        }
+        (Literal::Date(_) | Literal::Time(_) | Literal::DateTime(_), _) => {
+            quote_str(&lit.to_string())
+        }
+        _ => lit.to_string(),
+    }
+}
+
+// Quotes each of `columns` and joins them into a list.
+fn quote_all(columns: &[Rc<str>]) -> String {
+    let columns: Vec<String> =
+        columns.iter().map(|col| quote_ident(col)).collect();
+    columns.join(", ")
+}
+
+// Conditions for a value in `column` to lie within `min` and `max`, which are
+// already formatted as SQL values.
+fn bound_checks(
+    column: &str,
+    min: Option<String>,
+    max: Option<String>,
+) -> Vec<String> {
+    let mut checks = Vec::new();
+    if let Some(min) = min {
+        checks.push(format!("{column} >= {min}"));
+    }
+    if let Some(max) = max {
+        checks.push(format!("{column} <= {max}"));
+    }
+    checks
+}
+
+impl ToSql for IntDataType {
+    fn sql_type(&self, _dialect: SqlDialect) -> String {
+        "INTEGER".into()
+    }
+
+    fn sql_checks(&self, column: &str, _dialect: SqlDialect) -> Vec<String> {
+        let min = self.min.map(|val| val.to_string());
+        let max = self.max.map(|val| val.to_string());
+        bound_checks(column, min, max)
+    }
+}
+
+impl ToSql for LongDataType {
+    fn sql_type(&self, dialect: SqlDialect) -> String {
+        match dialect {
+            SqlDialect::Sqlite => "INTEGER".into(),
+            SqlDialect::Postgres => "BIGINT".into(),
+        }
+    }
+
+    fn sql_checks(&self, column: &str, _dialect: SqlDialect) -> Vec<String> {
+        let min = self.min.map(|val| val.to_string());
+        let max = self.max.map(|val| val.to_string());
+        bound_checks(column, min, max)
+    }
+}
+
+impl ToSql for DblDataType {
+    fn sql_type(&self, dialect: SqlDialect) -> String {
+        match dialect {
+            SqlDialect::Sqlite => "REAL".into(),
+            SqlDialect::Postgres => "DOUBLE PRECISION".into(),
+        }
+    }
+
+    fn sql_checks(&self, column: &str, _dialect: SqlDialect) -> Vec<String> {
+        let min = self.min.map(|val| val.to_string());
+        let max = self.max.map(|val| val.to_string());
+        bound_checks(column, min, max)
+    }
+}
+
+impl ToSql for DecDataType {
+    fn sql_type(&self, dialect: SqlDialect) -> String {
+        match dialect {
+            SqlDialect::Sqlite => "NUMERIC".into(),
+            SqlDialect::Postgres => {
+                format!("NUMERIC({}, {})", self.precision, self.scale)
+            }
+        }
+    }
+
+    fn sql_checks(&self, column: &str, dialect: SqlDialect) -> Vec<String> {
+        match dialect {
+            // limits the digits before the decimal point
+            SqlDialect::Sqlite => {
+                let digits = self.get_int_digits() as usize;
+                vec![format!("abs({column}) < 1{}", "0".repeat(digits))]
+            }
+            // the precision is part of the type
+            SqlDialect::Postgres => vec![],
+        }
+    }
+}
+
+impl ToSql for StrDataType {
+    fn sql_type(&self, _dialect: SqlDialect) -> String {
+        "TEXT".into()
+    }
+
+    fn sql_checks(&self, column: &str, dialect: SqlDialect) -> Vec<String> {
+        let length = format!("length({column})");
+        let min = self.min.map(|val| val.to_string());
+        let max = self.max.map(|val| val.to_string());
+        let mut checks = bound_checks(&length, min, max);
+        // SQLite has no built-in regex support, so patterns aren't checked
+        if let (SqlDialect::Postgres, Some(pattern)) = (dialect, &self.pattern)
+        {
+            checks.push(format!("{column} ~ {}", quote_str(pattern.as_str())));
+        }
+        checks
+    }
+}
+
+impl ToSql for BoolDataType {
+    fn sql_type(&self, dialect: SqlDialect) -> String {
+        match dialect {
+            SqlDialect::Sqlite => "INTEGER".into(),
+            SqlDialect::Postgres => "BOOLEAN".into(),
+        }
+    }
+
+    fn sql_checks(&self, column: &str, dialect: SqlDialect) -> Vec<String> {
+        match dialect {
+            SqlDialect::Sqlite => vec![format!("{column} IN (0, 1)")],
+            SqlDialect::Postgres => vec![],
+        }
+    }
+}
+
+impl ToSql for DateDataType {
+    fn sql_type(&self, dialect: SqlDialect) -> String {
+        match dialect {
+            SqlDialect::Sqlite => "TEXT".into(),
+            SqlDialect::Postgres => "DATE".into(),
+        }
+    }
+
+    fn sql_checks(&self, column: &str, dialect: SqlDialect) -> Vec<String> {
+        let min = self
+            .min
+            .map(|val| sql_literal(&Literal::Date(val), dialect));
+        let max = self
+            .max
+            .map(|val| sql_literal(&Literal::Date(val), dialect));
+        bound_checks(column, min, max)
+    }
+}
+
+impl ToSql for TimeDataType {
+    fn sql_type(&self, dialect: SqlDialect) -> String {
+        match dialect {
+            SqlDialect::Sqlite => "TEXT".into(),
+            SqlDialect::Postgres => "TIME".into(),
+        }
+    }
+
+    fn sql_checks(&self, column: &str, dialect: SqlDialect) -> Vec<String> {
+        let min = self
+            .min
+            .map(|val| sql_literal(&Literal::Time(val), dialect));
+        let max = self
+            .max
+            .map(|val| sql_literal(&Literal::Time(val), dialect));
+        bound_checks(column, min, max)
+    }
+}
+
+impl ToSql for DateTimeDataType {
+    fn sql_type(&self, dialect: SqlDialect) -> String {
+        match dialect {
+            SqlDialect::Sqlite => "TEXT".into(),
+            SqlDialect::Postgres => "TIMESTAMP".into(),
+        }
+    }
+
+    fn sql_checks(&self, column: &str, dialect: SqlDialect) -> Vec<String> {
+        let min = self
+            .min
+            .map(|val| sql_literal(&Literal::DateTime(val), dialect));
+        let max = self
+            .max
+            .map(|val| sql_literal(&Literal::DateTime(val), dialect));
+        bound_checks(column, min, max)
+    }
+}
+
+impl ToSql for EnumDataType {
+    fn sql_type(&self, _dialect: SqlDialect) -> String {
+        "TEXT".into()
+    }
+
+    fn sql_checks(&self, column: &str, _dialect: SqlDialect) -> Vec<String> {
+        let variants: Vec<String> =
+            self.variants.iter().map(|val| quote_str(val)).collect();
+        vec![format!("{column} IN ({})", variants.join(", "))]
+    }
+}
+
+impl Expr {
+    /// Formats the expression as a SQL condition. Compound operands are
+    /// always parenthesized, so SQL's precedence rules don't matter.
+    pub fn to_sql(&self, dialect: SqlDialect) -> String {
+        let operand = |expr: &Expr| match expr {
+            Expr::Literal(_) | Expr::Column(_) => expr.to_sql(dialect),
+            _ => format!("({})", expr.to_sql(dialect)),
+        };
+        match self {
+            Self::Literal(lit) => sql_literal(lit, dialect),
+            Self::Column(name) => quote_ident(name),
+            Self::Unary(UnaryOp::Not, expr) => format!("NOT {}", operand(expr)),
+            // `--` starts a comment, so a negative operand is parenthesized
+            Self::Unary(UnaryOp::Neg, expr) => match operand(expr) {
+                val if val.starts_with('-') => format!("-({val})"),
+                val => format!("-{val}"),
+            },
+            Self::Binary(op, l, r) => {
+                let op = match op {
+                    BinaryOp::Add => "+",
+                    BinaryOp::Sub => "-",
+                    BinaryOp::Mul => "*",
+                    BinaryOp::Div => "/",
+                    BinaryOp::Eq => "=",
+                    BinaryOp::Ne => "<>",
+                    BinaryOp::Lt => "<",
+                    BinaryOp::Le => "<=",
+                    BinaryOp::Gt => ">",
+                    BinaryOp::Ge => ">=",
+                    BinaryOp::And => "AND",
+                    BinaryOp::Or => "OR",
+                };
+                format!("{} {op} {}", operand(l), operand(r))
+            }
+            Self::IsNull(expr, negated) => match negated {
+                true => format!("{} IS NOT NULL", operand(expr)),
+                false => format!("{} IS NULL", operand(expr)),
+            },
+        }
+    }
+}
+
+impl ColumnSchema {
+    /// Formats the column as a SQL column definition. In Postgres, a column
+    /// with a named type refers to the domain created for it.
+    pub fn to_sql(&self, name: &str, dialect: SqlDialect) -> String {
+        let column = quote_ident(name);
+        // in Postgres, named types become domains that hold their own checks
+        let domain = match dialect {
+            SqlDialect::Postgres => self.type_alias.as_deref(),
+            SqlDialect::Sqlite => None,
+        };
+        let mut def = match domain {
+            Some(domain) => format!("{column} {}", quote_ident(domain)),
+            None => format!("{column} {}", self.column_type.sql_type(dialect)),
+        };
+        if !self.column_type.get_nullable() {
+            def.push_str(" NOT NULL");
+        }
+        if let Some(default) = &self.default_value {
+            def.push_str(&format!(
+                " DEFAULT {}",
+                sql_literal(default, dialect)
+            ));
+        }
+        let checks = self.column_type.sql_checks(&column, dialect);
+        if domain.is_none() && !checks.is_empty() {
+            def.push_str(&format!(" CHECK ({})", checks.join(" AND ")));
+        }
+        if let Some(fk) = &self.references {
+            def.push_str(&format!(
+                " REFERENCES {} ({})",
+                quote_ident(fk.get_table()),
+                quote_ident(fk.get_column())
+            ));
+        }
+        def
+    }
+}
+
+impl TableSchema {
+    /// Formats the table as a SQL `CREATE TABLE` statement.
+    pub fn to_sql(&self, name: &str, dialect: SqlDialect) -> String {
+        let mut defs: Vec<String> = self
+            .column_names
+            .iter()
+            .map(|col| self.columns[col].to_sql(col, dialect))
+            .collect();
+        if let Some(key) = &self.primary_key {
+            defs.push(format!("PRIMARY KEY ({})", quote_all(key)));
+        }
+        for columns in &self.unique {
+            defs.push(format!("UNIQUE ({})", quote_all(columns)));
+        }
+        for check in &self.checks {
+            defs.push(format!("CHECK ({})", check.to_sql(dialect)));
+        }
+
+        format!(
+            "CREATE TABLE {} (\n    {}\n);",
+            quote_ident(name),
+            defs.join(",\n    ")
+        )
+    }
+}
+
+impl SpreadsheetSchema {
+    /// Formats the named type as a Postgres `CREATE DOMAIN` statement.
+    /// Nullability is left to the columns that use it.
+    fn to_sql_domain(&self, name: &str) -> String {
+        let data_type = &self.types[name];
+        let dialect = SqlDialect::Postgres;
+        let checks = data_type.sql_checks("VALUE", dialect);
+        let checks = match checks.is_empty() {
+            true => String::new(),
+            false => format!(" CHECK ({})", checks.join(" AND ")),
+        };
+        format!(
+            "CREATE DOMAIN {} AS {}{checks};",
+            quote_ident(name),
+            data_type.sql_type(dialect)
+        )
+    }
+
+    /// Formats the schema as SQL `CREATE TABLE` statements, one per table.
+    /// For Postgres, these are preceded by a `CREATE DOMAIN` statement for
+    /// each named type.
+    pub fn to_sql(&self, dialect: SqlDialect) -> String {
+        let mut stmts: Vec<String> = Vec::new();
+        if dialect == SqlDialect::Postgres && !self.type_names.is_empty() {
+            let domains: Vec<String> = self
+                .type_names
+                .iter()
+                .map(|name| self.to_sql_domain(name))
+                .collect();
+            stmts.push(domains.join("\n"));
+        }
+        stmts.extend(
+            self.table_names
+                .iter()
+                .map(|name| self.tables[name].to_sql(name, dialect)),
+        );
+        stmts.join("\n\n") + "\n"
+    }
+}
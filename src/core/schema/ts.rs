@@ -0,0 +1,287 @@
+use crate::{
+    core::schema::{
+        BoolDataType, ColumnSchema, DateDataType, DateTimeDataType,
+        DblDataType, DecDataType, EnumDataType, IntDataType, LongDataType,
+        SpreadsheetSchema, StrDataType, TableSchema, TimeDataType,
+    },
+    json::ToJson,
+    ql::lex::Literal,
+    ts::{ToTypeScript, quote_ts_str, ts_ident, ts_idents, ts_property},
+};
+
+// Adds `| null` to the type of a nullable value.
+fn nullable_type(ts_type: &str, nullable: bool) -> String {
+    match nullable {
+        true => format!("{ts_type} | null"),
+        false => ts_type.to_string(),
+    }
+}
+
+// Appends the given Zod methods to `base`, keeping only the ones that are
+// set, followed by `.nullable()` if the value can be null.
+fn zod_chain(
+    base: &str,
+    methods: Vec<Option<String>>,
+    nullable: bool,
+) -> String {
+    let mut schema = base.to_string();
+    for method in methods.into_iter().flatten() {
+        schema.push('.');
+        schema.push_str(&method);
+    }
+    if nullable {
+        schema.push_str(".nullable()");
+    }
+    schema
+}
+
+// Bounds on a date or time, which are compared as strings since the formats
+// sort the same way as the values.
+fn temporal_bounds(
+    min: Option<Literal>,
+    max: Option<Literal>,
+) -> Vec<Option<String>> {
+    vec![
+        min.map(|min| {
+            let min = quote_ts_str(&min.to_string());
+            format!("refine((val) => val >= {min}, {{ message: \"Must be at least \" + {min} }})")
+        }),
+        max.map(|max| {
+            let max = quote_ts_str(&max.to_string());
+            format!("refine((val) => val <= {max}, {{ message: \"Must be at most \" + {max} }})")
+        }),
+    ]
+}
+
+impl ToTypeScript for IntDataType {
+    fn ts_type(&self) -> String {
+        nullable_type("number", self.nullable)
+    }
+
+    fn zod_schema(&self) -> String {
+        zod_chain(
+            "z.number()",
+            vec![
+                Some("int()".into()),
+                self.min.map(|min| format!("min({min})")),
+                self.max.map(|max| format!("max({max})")),
+            ],
+            self.nullable,
+        )
+    }
+}
+
+// JavaScript numbers are only exact up to 2^53, so longs are bigints. Zod
+// coerces the numbers or strings they're sent as.
+impl ToTypeScript for LongDataType {
+    fn ts_type(&self) -> String {
+        nullable_type("bigint", self.nullable)
+    }
+
+    fn zod_schema(&self) -> String {
+        zod_chain(
+            "z.coerce.bigint()",
+            vec![
+                self.min.map(|min| format!("min({min}n)")),
+                self.max.map(|max| format!("max({max}n)")),
+            ],
+            self.nullable,
+        )
+    }
+}
+
+impl ToTypeScript for DblDataType {
+    fn ts_type(&self) -> String {
+        nullable_type("number", self.nullable)
+    }
+
+    fn zod_schema(&self) -> String {
+        zod_chain(
+            "z.number()",
+            vec![
+                self.min.map(|min| format!("min({min})")),
+                self.max.map(|max| format!("max({max})")),
+            ],
+            self.nullable,
+        )
+    }
+}
+
+impl ToTypeScript for DecDataType {
+    fn ts_type(&self) -> String {
+        // decimals are sent as strings so that no digits are lost
+        nullable_type("string", self.nullable)
+    }
+
+    fn zod_schema(&self) -> String {
+        let pattern = quote_ts_str(&self.get_digits_pattern());
+        zod_chain(
+            "z.string()",
+            vec![Some(format!("regex(new RegExp({pattern}))"))],
+            self.nullable,
+        )
+    }
+}
+
+impl ToTypeScript for StrDataType {
+    fn ts_type(&self) -> String {
+        nullable_type("string", self.nullable)
+    }
+
+    // Zod counts lengths in UTF-16 code units rather than characters, so
+    // strings with characters outside the Basic Multilingual Plane can fail
+    // its checks while passing the schema's
+    fn zod_schema(&self) -> String {
+        zod_chain(
+            "z.string()",
+            vec![
+                self.min.map(|min| format!("min({min})")),
+                self.max.map(|max| format!("max({max})")),
+                self.pattern.as_ref().map(|pattern| {
+                    format!(
+                        "regex(new RegExp({}))",
+                        quote_ts_str(pattern.as_str())
+                    )
+                }),
+            ],
+            self.nullable,
+        )
+    }
+}
+
+impl ToTypeScript for BoolDataType {
+    fn ts_type(&self) -> String {
+        nullable_type("boolean", self.nullable)
+    }
+
+    fn zod_schema(&self) -> String {
+        zod_chain("z.boolean()", vec![], self.nullable)
+    }
+}
+
+impl ToTypeScript for DateDataType {
+    fn ts_type(&self) -> String {
+        nullable_type("string", self.nullable)
+    }
+
+    fn zod_schema(&self) -> String {
+        let mut methods = vec![Some(r"regex(/^\d{4}-\d{2}-\d{2}$/)".into())];
+        methods.extend(temporal_bounds(
+            self.min.map(Literal::Date),
+            self.max.map(Literal::Date),
+        ));
+        zod_chain("z.string()", methods, self.nullable)
+    }
+}
+
+impl ToTypeScript for TimeDataType {
+    fn ts_type(&self) -> String {
+        nullable_type("string", self.nullable)
+    }
+
+    fn zod_schema(&self) -> String {
+        let mut methods = vec![Some(r"regex(/^\d{2}:\d{2}:\d{2}$/)".into())];
+        methods.extend(temporal_bounds(
+            self.min.map(Literal::Time),
+            self.max.map(Literal::Time),
+        ));
+        zod_chain("z.string()", methods, self.nullable)
+    }
+}
+
+impl ToTypeScript for DateTimeDataType {
+    fn ts_type(&self) -> String {
+        nullable_type("string", self.nullable)
+    }
+
+    fn zod_schema(&self) -> String {
+        let mut methods = vec![Some(
+            r"regex(/^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}$/)".into(),
+        )];
+        methods.extend(temporal_bounds(
+            self.min.map(Literal::DateTime),
+            self.max.map(Literal::DateTime),
+        ));
+        zod_chain("z.string()", methods, self.nullable)
+    }
+}
+
+impl ToTypeScript for EnumDataType {
+    fn ts_type(&self) -> String {
+        let variants: Vec<String> =
+            self.variants.iter().map(|val| quote_ts_str(val)).collect();
+        nullable_type(&variants.join(" | "), self.nullable)
+    }
+
+    fn zod_schema(&self) -> String {
+        let variants: Vec<String> =
+            self.variants.iter().map(|val| quote_ts_str(val)).collect();
+        zod_chain(
+            &format!("z.enum([{}])", variants.join(", ")),
+            vec![],
+            self.nullable,
+        )
+    }
+}
+
+impl ColumnSchema {
+    /// The Zod schema for values of the column. Columns with a default may be
+    /// left out, and take the default when parsed.
+    pub fn to_zod_schema(&self) -> String {
+        let schema = self.column_type.zod_schema();
+        match &self.default_value {
+            Some(Literal::Long(val)) => format!("{schema}.default({val}n)"),
+            Some(val) => format!("{schema}.default({})", val.to_json()),
+            None => schema,
+        }
+    }
+}
+
+impl TableSchema {
+    /// Formats a row of the table as a TypeScript interface called `name`,
+    /// made into a valid identifier if it isn't one.
+    pub fn to_ts_interface(&self, name: &str) -> String {
+        let name = ts_ident(name);
+        let fields: Vec<String> = self
+            .column_names
+            .iter()
+            .map(|col| {
+                let ts_type = self.columns[col].column_type.ts_type();
+                format!("  {}: {ts_type};\n", ts_property(col))
+            })
+            .collect();
+        format!("export interface {name} {{\n{}}}", fields.concat())
+    }
+
+    /// Formats a Zod object schema for a row of the table, as a constant
+    /// called `name`, made into a valid identifier if it isn't one.
+    pub fn to_zod_schema(&self, name: &str) -> String {
+        let name = ts_ident(name);
+        let fields: Vec<String> = self
+            .column_names
+            .iter()
+            .map(|col| {
+                let schema = self.columns[col].to_zod_schema();
+                format!("  {}: {schema},\n", ts_property(col))
+            })
+            .collect();
+        format!("export const {name} = z.object({{\n{}}});", fields.concat())
+    }
+}
+
+impl SpreadsheetSchema {
+    /// Generates a TypeScript module with an interface for each table and a
+    /// Zod schema for validating its rows, named after the table with a
+    /// `Schema` suffix. Tables whose names make the same identifier are
+    /// numbered.
+    pub fn to_typescript(&self) -> String {
+        let mut decls = vec!["import { z } from \"zod\";".to_string()];
+        let idents = ts_idents(&self.table_names);
+        for (name, ident) in self.table_names.iter().zip(idents) {
+            let table = &self.tables[name];
+            decls.push(table.to_ts_interface(&ident));
+            decls.push(table.to_zod_schema(&format!("{ident}Schema")));
+        }
+        decls.join("\n\n") + "\n"
+    }
+}
@@ -0,0 +1,159 @@
+use std::{collections::HashMap, rc::Rc};
+
+use crate::{core::schema::TableSchema, ql::lex::Literal};
+
+/// A row of values, in the order given by `TableSchema::get_column_names`.
+/// `None` represents a null value.
+pub type Row = Vec<Option<Literal>>;
+
+/// Reports two rows that have the same values for a unique constraint (or the
+/// primary key).
+#[derive(Clone, Debug, PartialEq)]
+pub struct UniqueViolation {
+    columns: Vec<Rc<str>>,
+    first_row: usize,
+    duplicate_row: usize,
+}
+
+impl UniqueViolation {
+    pub fn new(
+        columns: Vec<Rc<str>>,
+        first_row: usize,
+        duplicate_row: usize,
+    ) -> Self {
+        Self {
+            columns,
+            first_row,
+            duplicate_row,
+        }
+    }
+
+    /// The columns of the violated constraint.
+    pub fn get_columns(&self) -> &[Rc<str>] {
+        &self.columns
+    }
+
+    /// The index of the first row holding the duplicated values.
+    pub fn get_first_row(&self) -> usize {
+        self.first_row
+    }
+
+    /// The index of the row that repeats them.
+    pub fn get_duplicate_row(&self) -> usize {
+        self.duplicate_row
+    }
+}
+
+// Canonical form of a literal for comparing rows, so that e.g. `1`, `1.0` and
+// `1.00` are considered the same value, as are a date and a string holding
+// the same date.
+fn unique_key(lit: &Literal) -> String {
+    lit.get_dec()
+        .map(|val| val.normalize().to_string())
+        .or_else(|| lit.get_date().map(|val| val.to_string()))
+        .or_else(|| lit.get_time().map(|val| val.to_string()))
+        .or_else(|| lit.get_datetime().map(|val| val.to_string()))
+        .unwrap_or_else(|| format!("{lit:?}"))
+}
+
+impl TableSchema {
+    /// Returns the positions of `columns` in a row, or `None` if any of them
+    /// don't exist.
+    fn get_column_indices(&self, columns: &[Rc<str>]) -> Option<Vec<usize>> {
+        columns
+            .iter()
+            .map(|name| self.get_column_index(name))
+            .collect()
+    }
+
+    /// Checks `rows` against the primary key and unique constraints, returning
+    /// one violation for every row that duplicates an earlier one. As in SQL,
+    /// rows with a null in any of the constraint's columns are never
+    /// considered duplicates. Constraints on unknown columns, which a valid
+    /// schema doesn't have, are skipped.
+    pub fn find_unique_violations(&self, rows: &[Row]) -> Vec<UniqueViolation> {
+        let mut index = UniqueIndex::new(self);
+        let mut violations = Vec::new();
+        for (row_idx, row) in rows.iter().enumerate() {
+            violations.extend(index.find_violations(row, row_idx));
+            index.add(row, row_idx);
+        }
+        violations
+    }
+}
+
+// The rows seen so far for one constraint, keyed by their values.
+struct IndexedConstraint {
+    columns: Vec<Rc<str>>,
+    indices: Vec<usize>,
+    seen: HashMap<Vec<String>, usize>,
+}
+
+impl IndexedConstraint {
+    // The key of `row` for this constraint, or `None` if any of its values
+    // are null.
+    fn get_key(&self, row: &Row) -> Option<Vec<String>> {
+        self.indices
+            .iter()
+            .map(|i| row.get(*i).and_then(|val| val.as_ref()))
+            .map(|val| val.map(unique_key))
+            .collect()
+    }
+}
+
+/// Checks rows against the primary key and unique constraints of a table one
+/// at a time, e.g. as they're imported.
+pub struct UniqueIndex {
+    constraints: Vec<IndexedConstraint>,
+}
+
+impl UniqueIndex {
+    /// Creates an index for the constraints of `schema`, holding no rows.
+    /// Constraints on unknown columns are skipped.
+    pub fn new(schema: &TableSchema) -> Self {
+        let constraints = schema
+            .primary_key
+            .iter()
+            .chain(schema.unique.iter())
+            .filter_map(|columns| {
+                Some(IndexedConstraint {
+                    columns: columns.clone(),
+                    indices: schema.get_column_indices(columns)?,
+                    seen: HashMap::new(),
+                })
+            })
+            .collect();
+        Self { constraints }
+    }
+
+    /// Returns a violation for each constraint on which `row`, at `row_idx`,
+    /// duplicates a row already added. The row itself isn't added.
+    pub fn find_violations(
+        &self,
+        row: &Row,
+        row_idx: usize,
+    ) -> Vec<UniqueViolation> {
+        self.constraints
+            .iter()
+            .filter_map(|constraint| {
+                let first_row =
+                    constraint.seen.get(&constraint.get_key(row)?)?;
+                Some(UniqueViolation::new(
+                    constraint.columns.clone(),
+                    *first_row,
+                    row_idx,
+                ))
+            })
+            .collect()
+    }
+
+    /// Adds `row`, at `row_idx`. Values it duplicates stay with the first row
+    /// that held them.
+    pub fn add(&mut self, row: &Row, row_idx: usize) {
+        for constraint in &mut self.constraints {
+            if let Some(key) = constraint.get_key(row) {
+                constraint.seen.entry(key).or_insert(row_idx);
+            }
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::rc::Rc;
+
+use crate::{core::schema::TableSchema, ql::lex::Literal};
+
+/// The kind of constraint that a value failed to satisfy.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConstraintKind {
+    // a null value in a non-nullable column
+    Null,
+    Min,
+    Max,
+    // a value of the wrong type for the column
+    TypeMismatch,
+    // a string that doesn't match the column's pattern
+    Pattern,
+    // a decimal with too many decimal places
+    Scale,
+    // a decimal with too many digits before the decimal point
+    Precision,
+    // a string that isn't one of the enum's variants
+    Variant,
+}
+
+/// Reports a single cell of a row that failed validation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CellViolation {
+    column: Rc<str>,
+    kind: ConstraintKind,
+    value: Option<Literal>,
+    message: String,
+}
+
+impl CellViolation {
+    pub fn new(
+        column: Rc<str>,
+        kind: ConstraintKind,
+        value: Option<Literal>,
+        message: String,
+    ) -> Self {
+        Self {
+            column,
+            kind,
+            value,
+            message,
+        }
+    }
+
+    /// The name of the column holding the bad value.
+    pub fn get_column(&self) -> &str {
+        &self.column
+    }
+
+    /// The kind of constraint the value failed.
+    pub fn get_kind(&self) -> ConstraintKind {
+        self.kind
+    }
+
+    /// The offending value, or `None` if it was null.
+    pub fn get_value(&self) -> Option<&Literal> {
+        self.value.as_ref()
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl TableSchema {
+    /// Validates each value of `row` against its column's type, returning a
+    /// violation for every bad cell rather than stopping at the first. Missing
+    /// trailing values are treated as nulls.
+    pub fn validate_row(&self, row: &[Option<Literal>]) -> Vec<CellViolation> {
+        let mut violations = Vec::new();
+
+        for (idx, column_name) in self.column_names.iter().enumerate() {
+            let column = &self.columns[column_name];
+            let value = row.get(idx).cloned().flatten();
+            if let Err(e) = column.get_type().validate_literal(value.as_ref()) {
+                // validators only report errors about values, but fall back
+                // to a type mismatch for anything else
+                let kind = e
+                    .get_constraint_kind()
+                    .unwrap_or(ConstraintKind::TypeMismatch);
+                violations.push(CellViolation::new(
+                    column_name.clone(),
+                    kind,
+                    value,
+                    e.to_string(),
+                ));
+            }
+        }
+
+        violations
+    }
+}
@@ -0,0 +1,216 @@
+use std::fmt::Display;
+
+use crate::core::schema::violation::ConstraintKind;
+
+/// Everything that can go wrong validating values against a schema,
+/// validating a schema, or parsing one. Values, bounds and tokens are kept
+/// as text so that errors can be sent between threads.
+///
+/// Validators return it directly. Schema validation and the parser wrap it
+/// in a `SubjectError` or `TokenError` saying where it occurred, and the
+/// `anyhow::Error`s from parsing a schema from text downcast to it, as do
+/// those from loading a schema from JSON or evaluating a check where one of
+/// its variants applies. More variants may be added.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum SchemaError {
+    /// A null value for a non-nullable type.
+    NullViolation,
+    /// A value below the type's minimum.
+    BelowMin { min: String, value: String },
+    /// A value above the type's maximum.
+    AboveMax { max: String, value: String },
+    /// A string shorter than the type's minimum length.
+    TooShort { min: usize, len: usize },
+    /// A string longer than the type's maximum length.
+    TooLong { max: usize, len: usize },
+    /// A literal of the wrong type, where `expected` is the type's name.
+    TypeMismatch { expected: String, found: String },
+    /// A string that doesn't match the type's pattern.
+    PatternMismatch { pattern: String, value: String },
+    /// A decimal with more than `scale` decimal places.
+    TooManyDecimals { scale: u32, value: String },
+    /// A decimal with more than `digits` digits before the decimal point.
+    TooManyDigits { digits: u32, value: String },
+    /// A string that isn't one of the enum's variants.
+    UnknownVariant { value: String },
+    /// A type whose minimum is greater than its maximum.
+    InvalidBounds { min: String, max: String },
+    /// Any other type that can't be satisfied, e.g. an enum with no
+    /// variants.
+    InvalidType { message: String },
+    /// A column default that isn't a valid value of the column's type.
+    InvalidDefault {
+        value: String,
+        error: Box<SchemaError>,
+    },
+    /// A primary key, unique constraint or check that doesn't fit its table.
+    InvalidConstraint { message: String },
+    /// A check that refers to a column its table doesn't have.
+    UnknownCheckColumn { column: String },
+    /// A check expression whose operands have different types.
+    MismatchedOperands {
+        expr: String,
+        left: String,
+        right: String,
+    },
+    /// A check expression of the wrong type, e.g. a check that isn't a
+    /// boolean or arithmetic on strings.
+    InvalidExprType {
+        expr: String,
+        expected: String,
+        found: String,
+    },
+    /// A foreign key from `column` (as `table.column`) to a missing table.
+    UnknownTable { column: String, table: String },
+    /// A foreign key from `column` to a missing `target` column.
+    UnknownColumn { column: String, target: String },
+    /// A foreign key between columns of incompatible types.
+    IncompatibleReference {
+        column: String,
+        column_type: String,
+        target: String,
+        target_type: String,
+    },
+    /// A type or table name that's already taken.
+    DuplicateSymbol { name: String },
+    /// Two columns of a table with the same name.
+    DuplicateColumn { table: String, column: String },
+    /// A table with more than one primary key.
+    DuplicatePrimaryKey { table: String },
+    /// A type name that isn't built in or declared.
+    UnknownType { name: String },
+    /// Source text that isn't a valid token, e.g. an unterminated string.
+    InvalidToken { text: String, message: String },
+    /// A token other than the one the parser expected, or `None` for the end
+    /// of the schema.
+    UnexpectedToken {
+        expected: String,
+        found: Option<String>,
+    },
+    /// A literal that can't be read as `expected`, e.g. an int bound.
+    InvalidLiteral { expected: String, found: String },
+    /// A string pattern that isn't a valid regex.
+    InvalidPattern { pattern: String, message: String },
+}
+
+impl SchemaError {
+    /// The kind of constraint a value failed, or `None` if the error isn't
+    /// about a value.
+    pub fn get_constraint_kind(&self) -> Option<ConstraintKind> {
+        match self {
+            Self::NullViolation => Some(ConstraintKind::Null),
+            Self::BelowMin { .. } | Self::TooShort { .. } => {
+                Some(ConstraintKind::Min)
+            }
+            Self::AboveMax { .. } | Self::TooLong { .. } => {
+                Some(ConstraintKind::Max)
+            }
+            Self::TypeMismatch { .. } => Some(ConstraintKind::TypeMismatch),
+            Self::PatternMismatch { .. } => Some(ConstraintKind::Pattern),
+            Self::TooManyDecimals { .. } => Some(ConstraintKind::Scale),
+            Self::TooManyDigits { .. } => Some(ConstraintKind::Precision),
+            Self::UnknownVariant { .. } => Some(ConstraintKind::Variant),
+            _ => None,
+        }
+    }
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NullViolation => write!(f, "Required value was null!"),
+            Self::BelowMin { min, value } => {
+                write!(f, "Minimum value {min} (entered {value})")
+            }
+            Self::AboveMax { max, value } => {
+                write!(f, "Maximum value {max} (entered {value})")
+            }
+            Self::TooShort { min, .. } => write!(f, "Minimum length {min}"),
+            Self::TooLong { max, .. } => write!(f, "Maximum length {max}"),
+            Self::TypeMismatch { expected, found } => write!(
+                f,
+                "Couldn't validate literal {found} against {expected} type."
+            ),
+            Self::PatternMismatch { pattern, value } => {
+                write!(f, "Value '{value}' doesn't match pattern \"{pattern}\"")
+            }
+            Self::TooManyDecimals { scale, value } => {
+                write!(f, "Maximum {scale} decimal places (entered {value})")
+            }
+            Self::TooManyDigits { digits, value } => write!(
+                f,
+                "Maximum {digits} digits before the decimal point (entered {value})"
+            ),
+            Self::UnknownVariant { value } => {
+                write!(f, "Value '{value}' is not one of the enum variants.")
+            }
+            Self::InvalidBounds { min, max } => {
+                write!(f, "Can't have min ({min}) > max ({max})")
+            }
+            Self::InvalidType { message }
+            | Self::InvalidConstraint { message } => write!(f, "{message}"),
+            Self::UnknownCheckColumn { column } => {
+                write!(f, "Check references unknown column '{column}'")
+            }
+            Self::MismatchedOperands { expr, left, right } => write!(
+                f,
+                "Mismatched operands in '{expr}' ({left} and {right})"
+            ),
+            Self::InvalidExprType {
+                expr,
+                expected,
+                found,
+            } => write!(
+                f,
+                "'{expr}' must be a {expected} expression, got {found}"
+            ),
+            Self::InvalidDefault { value, error } => {
+                write!(f, "Invalid default value {value}: {error}")
+            }
+            Self::UnknownTable { column, table } => write!(
+                f,
+                "Column '{column}' references unknown table '{table}'"
+            ),
+            Self::UnknownColumn { column, target } => write!(
+                f,
+                "Column '{column}' references unknown column '{target}'"
+            ),
+            Self::IncompatibleReference {
+                column,
+                column_type,
+                target,
+                target_type,
+            } => write!(
+                f,
+                "Column '{column}' ({column_type}) can't reference '{target}' ({target_type})"
+            ),
+            Self::DuplicateSymbol { name } => {
+                write!(f, "Symbol {name} is already assigned!")
+            }
+            Self::DuplicateColumn { column, .. } => {
+                write!(f, "Can't have multiple columns named '{column}'")
+            }
+            Self::DuplicatePrimaryKey { table } => {
+                write!(f, "Can't have multiple primary keys in table '{table}'")
+            }
+            Self::UnknownType { name } => {
+                write!(f, "Unrecognised type name {name}")
+            }
+            Self::InvalidToken { text, message } => {
+                write!(f, "Couldn't lex '{text}': {message}")
+            }
+            Self::UnexpectedToken { expected, .. } => {
+                write!(f, "Couldn't get {expected}")
+            }
+            Self::InvalidLiteral { expected, .. } => {
+                write!(f, "Couldn't parse {expected} literal!")
+            }
+            Self::InvalidPattern { pattern, message } => {
+                write!(f, "Invalid pattern \"{pattern}\": {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
@@ -4,3 +4,13 @@ use serde_json::Value;
 pub trait ToJson {
     fn to_json(&self) -> Value;
 }
+
+/// Counterpart to `ToJson`, reading a value back from the JSON it emits.
+pub trait FromJson: Sized {
+    fn from_json(json: &Value) -> anyhow::Result<Self>;
+}
+
+/// Describes the values of a type as a JSON Schema (draft 2020-12).
+pub trait ToJsonSchema {
+    fn to_json_schema(&self) -> Value;
+}
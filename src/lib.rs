@@ -2,23 +2,130 @@ use rlrl::parse::TokenQueue;
 
 use crate::{
     core::schema::SpreadsheetSchema,
-    json::ToJson,
-    ql::{lex::setup_lexer, parse::parse_spreadsheet_schema},
+    json::{FromJson, ToJson},
+    ql::{
+        diagnostic::{Diagnostic, Severity},
+        lex::{lex_with_recovery, lex_with_spans},
+        parse::{
+            parse_spreadsheet_schema, parse_spreadsheet_schema_with_recovery,
+        },
+        span::{SourceError, Subject, SubjectError, TokenError},
+    },
 };
 
+pub mod codegen;
 pub mod core;
+pub mod error;
 pub mod json;
 pub mod ql;
+pub mod rust;
+pub mod sql;
+pub mod ts;
+
+// re-exported for the code generated by `core::schema::rust`
+pub use chrono;
+pub use rust_decimal;
+pub use serde;
+pub use serde_json;
+
+// lets the tests include generated code, which refers to `::ftables_lib`
+#[cfg(test)]
+extern crate self as ftables_lib;
 
 #[cfg(test)]
 mod tests;
 
+/// Parses and validates a schema. Errors are reported with the line and
+/// column they occurred at, as a `SourceError`, wherever they can be traced
+/// back to the source.
 pub fn parse_valid_schema_from_str(
     s: &str,
 ) -> anyhow::Result<SpreadsheetSchema> {
-    let tq = TokenQueue::from(setup_lexer().lex(s)?);
-    let schema = parse_spreadsheet_schema(&tq)?;
-    schema.validate_spreadsheet_schema()?;
+    let (tokens, spans) = lex_with_spans(s)?;
+    let locate = |e| SourceError::locate(e, s, &tokens, &spans);
+    let tq = TokenQueue::from(tokens.clone());
+    let schema =
+        parse_spreadsheet_schema(&tq).map_err(|e| locate(e.into_error()))?;
+    schema
+        .validate_spreadsheet_schema()
+        .map_err(|e| locate(e.into_error()))?;
+    Ok(schema)
+}
+
+/// Parses and validates a schema, carrying on past errors so that every
+/// problem in it is reported at once, in the order they appear in `s`. The
+/// schema is only returned if none of the diagnostics are errors.
+pub fn check_schema(s: &str) -> (Option<SpreadsheetSchema>, Vec<Diagnostic>) {
+    // the catch-all lexer rules match any text, so this only fails if the
+    // lexer itself does
+    let (tokens, spans, lex_errors) = match lex_with_recovery(s) {
+        Ok(lexed) => lexed,
+        Err(e) => {
+            let diagnostic =
+                Diagnostic::new(Severity::Error, e.to_string(), None);
+            return (None, vec![diagnostic]);
+        }
+    };
+    let tq = TokenQueue::from(tokens.clone());
+    let (schema, parse_errors) = parse_spreadsheet_schema_with_recovery(&tq);
+    let errors: Vec<anyhow::Error> = parse_errors
+        .into_iter()
+        .map(TokenError::into_error)
+        .chain(
+            schema
+                .find_schema_errors()
+                .into_iter()
+                .map(SubjectError::into_error),
+        )
+        .collect();
+
+    // bad tokens are left out, so the parser may report errors around them
+    // too
+    let mut diagnostics: Vec<Diagnostic> = lex_errors
+        .into_iter()
+        .map(|(span, e)| {
+            Diagnostic::new(Severity::Error, e.to_string(), Some(span))
+        })
+        .collect();
+    diagnostics.extend(
+        errors.iter().map(|e| {
+            Diagnostic::from_error(Severity::Error, e, &tokens, &spans)
+        }),
+    );
+    for type_name in schema.find_unused_types() {
+        let span = Subject::Type(type_name.to_string())
+            .find(&tokens)
+            .and_then(|idx| spans.get(idx).copied());
+        diagnostics.push(Diagnostic::new(
+            Severity::Warning,
+            format!("Type '{type_name}' is never used"),
+            span,
+        ));
+    }
+
+    // in source order, with any that couldn't be located last
+    diagnostics.sort_by_key(|diagnostic| match diagnostic.get_span() {
+        Some(span) => (false, span.get_start()),
+        None => (true, 0),
+    });
+
+    let has_errors = diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.get_severity() == Severity::Error);
+    match has_errors {
+        true => (None, diagnostics),
+        false => (Some(schema), diagnostics),
+    }
+}
+
+/// Reads a schema from the JSON that `compile_schema` emits, and validates it.
+pub fn parse_valid_schema_from_json(
+    json: &serde_json::Value,
+) -> anyhow::Result<SpreadsheetSchema> {
+    let schema = SpreadsheetSchema::from_json(json)?;
+    schema
+        .validate_spreadsheet_schema()
+        .map_err(SubjectError::into_error)?;
     Ok(schema)
 }
 
@@ -0,0 +1,87 @@
+use std::fmt::Display;
+
+use crate::ql::{
+    lex::Token,
+    span::{SourceError, Span, find_span},
+};
+
+/// How serious a diagnostic is. Only errors stop a schema from being used.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A problem found while checking a schema.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    severity: Severity,
+    message: String,
+    span: Option<Span>,
+}
+
+impl Diagnostic {
+    pub fn new(
+        severity: Severity,
+        message: String,
+        span: Option<Span>,
+    ) -> Self {
+        Self {
+            severity,
+            message,
+            span,
+        }
+    }
+
+    /// Builds a diagnostic from an error raised while parsing or validating,
+    /// locating it with the `tokens` and `spans` the source was lexed into.
+    pub fn from_error(
+        severity: Severity,
+        error: &anyhow::Error,
+        tokens: &[Token],
+        spans: &[Span],
+    ) -> Self {
+        Self::new(severity, error.to_string(), find_span(error, tokens, spans))
+    }
+
+    pub fn get_severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+
+    /// The part of the source the diagnostic is about, if it's known.
+    pub fn get_span(&self) -> Option<Span> {
+        self.span
+    }
+
+    /// Formats the diagnostic with the line and column it occurred at in
+    /// `source`, and a snippet of the offending line, if it has a span.
+    pub fn render(&self, source: &str) -> String {
+        match self.span {
+            Some(span) => format!(
+                "{}: {}",
+                self.severity,
+                SourceError::new(span, source, self.message.clone())
+            ),
+            None => self.to_string(),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
@@ -1,12 +1,68 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 use rlrl::lex::*;
-use std::{fmt::Display, rc::Rc};
+use rust_decimal::{Decimal, prelude::ToPrimitive};
+use std::{fmt::Display, rc::Rc, str::FromStr};
+
+use crate::{
+    error::SchemaError,
+    ql::span::{SourceError, Span},
+};
+
+/// ISO-8601 format used to read and write `Literal::Date` values.
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+/// ISO-8601 format used to read and write `Literal::Time` values.
+pub const TIME_FORMAT: &str = "%H:%M:%S";
+/// ISO-8601 format used to read and write `Literal::DateTime` values.
+pub const DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// Words reserved by the lexer, which can't be used as identifiers, along
+/// with the token each one is lexed as. The lexer's rules for them are built
+/// from this list.
+///
+/// The words that start table constraints (`primary`, `key` and `check`)
+/// and the operators in checks (`and`, `or`, `not`, `is` and `null`) aren't
+/// reserved. They're lexed as identifiers, and only treated as keywords
+/// where the parser expects them.
+pub const KEYWORDS: [(&str, Token); 9] = [
+    ("type", Token::TypeKwd),
+    ("tab", Token::TableKwd),
+    ("table", Token::TableKwd),
+    ("schema", Token::SchemaKwd),
+    ("sch", Token::SchemaKwd),
+    ("references", Token::ReferencesKwd),
+    ("unique", Token::UniqueKwd),
+    ("true", Token::Literal(Literal::Bool(true))),
+    ("false", Token::Literal(Literal::Bool(false))),
+];
+
+/// Whether `word` is reserved by the lexer.
+pub fn is_keyword(word: &str) -> bool {
+    KEYWORDS.iter().any(|(keyword, _)| *keyword == word)
+}
+
+/// Formats a table, column or type name so that it's read back as the same
+/// name: as an identifier where possible, and as a string otherwise.
+pub fn quote_name(name: &str) -> String {
+    let is_ident = name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    match is_ident && !is_keyword(name) {
+        true => name.to_string(),
+        false => format!("\"{name}\""),
+    }
+}
 
 /// A literal in the query language.
 #[derive(Clone, Debug, PartialEq)]
 pub enum Literal {
     Int(i32),
+    Long(i64),
     Dbl(f64),
+    Dec(Decimal),
     Str(Rc<str>),
+    Bool(bool),
+    Date(NaiveDate),
+    Time(NaiveTime),
+    DateTime(NaiveDateTime),
 }
 
 impl Literal {
@@ -42,19 +98,115 @@ impl Literal {
         }
     }
 
+    pub fn is_i64(&self) -> bool {
+        self.get_i64().is_some()
+    }
+
+    /// Copies the int value if `self` is a `Literal::Long` or `Literal::Int`,
+    /// otherwise returns `None`.
+    pub fn get_i64(&self) -> Option<i64> {
+        match self {
+            Self::Long(val) => Some(*val),
+            Self::Int(val) => Some(*val as i64),
+            _ => None,
+        }
+    }
+
     pub fn is_f64(&self) -> bool {
         match self {
-            Self::Dbl(_) | Self::Int(_) => true,
+            Self::Dbl(_) | Self::Int(_) | Self::Long(_) | Self::Dec(_) => true,
             _ => false,
         }
     }
 
-    /// Clones the double value if `self` is a `Literal::Dbl`, otherwise returns
-    /// `None`.
+    /// Clones the double value if `self` is a `Literal::Dbl`, or converts it if
+    /// `self` is any other numeric literal. Otherwise returns `None`.
     pub fn get_f64(&self) -> Option<f64> {
         match self {
             Self::Dbl(val) => Some(val.clone()),
             Self::Int(val) => Some(*val as f64),
+            Self::Long(val) => Some(*val as f64),
+            Self::Dec(val) => val.to_f64(),
+            _ => None,
+        }
+    }
+
+    pub fn is_dec(&self) -> bool {
+        self.get_dec().is_some()
+    }
+
+    /// Copies the decimal value if `self` is a `Literal::Dec`, or converts it
+    /// if `self` is any other numeric literal. Doubles are converted via their
+    /// shortest round-trip representation, so `0.1` becomes exactly `0.1`.
+    /// Otherwise returns `None`.
+    pub fn get_dec(&self) -> Option<Decimal> {
+        match self {
+            Self::Dec(val) => Some(*val),
+            Self::Int(val) => Some(Decimal::from(*val)),
+            Self::Long(val) => Some(Decimal::from(*val)),
+            Self::Dbl(val) => Decimal::from_str(&val.to_string()).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn is_bool(&self) -> bool {
+        matches!(self, Self::Bool(_))
+    }
+
+    /// Copies the boolean value if `self` is a `Literal::Bool`, otherwise
+    /// returns `None`.
+    pub fn get_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(val) => Some(*val),
+            _ => None,
+        }
+    }
+
+    pub fn is_date(&self) -> bool {
+        self.get_date().is_some()
+    }
+
+    /// Copies the date value if `self` is a `Literal::Date`, or parses it if
+    /// `self` is a `Literal::Str` holding an ISO-8601 date. Otherwise returns
+    /// `None`.
+    pub fn get_date(&self) -> Option<NaiveDate> {
+        match self {
+            Self::Date(val) => Some(*val),
+            Self::Str(val) => NaiveDate::parse_from_str(val, DATE_FORMAT).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn is_time(&self) -> bool {
+        self.get_time().is_some()
+    }
+
+    /// Copies the time value if `self` is a `Literal::Time`, or parses it if
+    /// `self` is a `Literal::Str` holding a time in `TIME_FORMAT`. Fractional
+    /// seconds aren't accepted, since they couldn't be written back.
+    /// Otherwise returns `None`.
+    pub fn get_time(&self) -> Option<NaiveTime> {
+        match self {
+            Self::Time(val) => Some(*val),
+            Self::Str(val) => NaiveTime::parse_from_str(val, TIME_FORMAT).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn is_datetime(&self) -> bool {
+        self.get_datetime().is_some()
+    }
+
+    /// Copies the datetime value if `self` is a `Literal::DateTime`, or parses
+    /// it if `self` is a `Literal::Str` holding a datetime in
+    /// `DATETIME_FORMAT`, without fractional seconds. Otherwise returns
+    /// `None`.
+    pub fn get_datetime(&self) -> Option<NaiveDateTime> {
+        match self {
+            Self::DateTime(val) => Some(*val),
+            Self::Str(val) => {
+                NaiveDateTime::parse_from_str(val, DATETIME_FORMAT).ok()
+            }
             _ => None,
         }
     }
@@ -64,8 +216,16 @@ impl Display for Literal {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Literal::Int(val) => write!(f, "{val}"),
+            Literal::Long(val) => write!(f, "{val}"),
             Literal::Dbl(val) => write!(f, "{val}"),
+            Literal::Dec(val) => write!(f, "{val}"),
             Literal::Str(val) => write!(f, "{val}"),
+            Literal::Bool(val) => write!(f, "{val}"),
+            Literal::Date(val) => write!(f, "{}", val.format(DATE_FORMAT)),
+            Literal::Time(val) => write!(f, "{}", val.format(TIME_FORMAT)),
+            Literal::DateTime(val) => {
+                write!(f, "{}", val.format(DATETIME_FORMAT))
+            }
         }
     }
 }
@@ -83,11 +243,19 @@ pub enum Token {
     Comma,
     Equals,
     QMark,
+    Tilde,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    NotEquals,
 
     // kwds
     TypeKwd,
     TableKwd,
     SchemaKwd,
+    ReferencesKwd,
+    UniqueKwd,
 
     // ident
     Ident(Rc<str>),
@@ -132,56 +300,174 @@ impl Token {
 
 /// Function to setup the lexer for testing
 pub fn setup_lexer() -> Lexer<Token> {
-    let mut lexer: Lexer<Token> = Lexer::new();
+    build_lexer(|result, _| result)
+}
+
+// Whitespace and comments are kept as `None` tokens, and each token carries
+// its length, so that the tokens' positions can be worked out. Errors are
+// kept as tokens too, so that they can be located.
+type SpannedToken = (Option<anyhow::Result<Token>>, usize);
+
+/// Lexes `s`, returning the tokens along with the span of each one. An
+/// error downcasts to a `SchemaError`, and to a `SourceError` saying where
+/// it occurred.
+pub fn lex_with_spans(s: &str) -> anyhow::Result<(Vec<Token>, Vec<Span>)> {
+    let (tokens, spans, errors) = lex_with_recovery(s)?;
+    match errors.into_iter().next() {
+        Some((span, error)) => {
+            let located = SourceError::new(span, s, error.to_string());
+            Err(anyhow::Error::new(error).context(located))
+        }
+        None => Ok((tokens, spans)),
+    }
+}
+
+/// Text that isn't a valid token, along with where it is.
+pub type LexError = (Span, SchemaError);
+
+/// Lexes `s`, skipping past text that isn't a valid token so that every
+/// such error is found. Returns the tokens and their spans along with the
+/// errors.
+pub fn lex_with_recovery(
+    s: &str,
+) -> anyhow::Result<(Vec<Token>, Vec<Span>, Vec<LexError>)> {
+    let lexer: Lexer<SpannedToken> = build_lexer(|result, len| match result {
+        LexResult::Token(token) => LexResult::Token((Some(Ok(token)), len)),
+        LexResult::Ignore => LexResult::Token((None, len)),
+        LexResult::Error(e) => LexResult::Token((Some(Err(e)), len)),
+    });
+
+    let mut tokens = Vec::new();
+    let mut spans = Vec::new();
+    let mut errors = Vec::new();
+    let mut pos = 0;
+    for (token, len) in lexer.lex(s)? {
+        let span = Span::new(pos, pos + len);
+        match token {
+            Some(Ok(token)) => {
+                tokens.push(token);
+                spans.push(span);
+            }
+            Some(Err(e)) => {
+                let error = SchemaError::InvalidToken {
+                    text: s[pos..pos + len].into(),
+                    message: e.to_string(),
+                };
+                errors.push((span, error));
+            }
+            None => {}
+        }
+        pos += len;
+    }
+    Ok((tokens, spans, errors))
+}
+
+// Sets up the lexer's rules, passing each result through `wrap` along with
+// the length of the text it matched.
+fn build_lexer<T: 'static>(
+    wrap: fn(LexResult<Token>, usize) -> LexResult<T>,
+) -> Lexer<T> {
+    let mut lexer: Lexer<T> = Lexer::new();
+
+    macro_rules! add_rule {
+        ($pat:expr, |_| $body:expr $(,)?) => {
+            add_rule!($pat, |re_match| $body)
+        };
+        ($pat:expr, |$re_match:ident| $body:expr $(,)?) => {
+            lexer.add_rule($pat, move |$re_match| {
+                let len = $re_match.len();
+                wrap($body, len)
+            })
+        };
+    }
 
     // comments
-    lexer.add_rule(r"//[^\n\r]*", |_| LexResult::Ignore);
-    lexer.add_rule(r"/\*[^*]*\*/", |_| LexResult::Ignore);
+    add_rule!(r"//[^\n\r]*", |_| LexResult::Ignore);
+    add_rule!(r"/\*[^*]*\*/", |_| LexResult::Ignore);
 
     // whitespace
-    lexer.add_rule(r"[\s]+", |_| LexResult::Ignore);
+    add_rule!(r"[\s]+", |_| LexResult::Ignore);
 
     // chars
-    lexer.add_rule(r"\(", |_| LexResult::Token(Token::OParen));
-    lexer.add_rule(r"\)", |_| LexResult::Token(Token::CParen));
-    lexer.add_rule(r"<", |_| LexResult::Token(Token::OAngle));
-    lexer.add_rule(r">", |_| LexResult::Token(Token::CAngle));
-    lexer.add_rule(r":", |_| LexResult::Token(Token::Colon));
-    lexer.add_rule(r";", |_| LexResult::Token(Token::Semicolon));
-    lexer.add_rule(r"\,", |_| LexResult::Token(Token::Comma));
-    lexer.add_rule(r"=", |_| LexResult::Token(Token::Equals));
-    lexer.add_rule(r"\?", |_| LexResult::Token(Token::QMark));
+    add_rule!(r"\(", |_| LexResult::Token(Token::OParen));
+    add_rule!(r"\)", |_| LexResult::Token(Token::CParen));
+    add_rule!(r"<", |_| LexResult::Token(Token::OAngle));
+    add_rule!(r">", |_| LexResult::Token(Token::CAngle));
+    add_rule!(r":", |_| LexResult::Token(Token::Colon));
+    add_rule!(r";", |_| LexResult::Token(Token::Semicolon));
+    add_rule!(r"\,", |_| LexResult::Token(Token::Comma));
+    add_rule!(r"=", |_| LexResult::Token(Token::Equals));
+    add_rule!(r"\?", |_| LexResult::Token(Token::QMark));
+    add_rule!(r"~", |_| LexResult::Token(Token::Tilde));
+    add_rule!(r"\+", |_| LexResult::Token(Token::Plus));
+    add_rule!(r"-", |_| LexResult::Token(Token::Minus));
+    add_rule!(r"\*", |_| LexResult::Token(Token::Star));
+    add_rule!(r"/", |_| LexResult::Token(Token::Slash));
+    add_rule!(r"!=", |_| LexResult::Token(Token::NotEquals));
 
-    // kwds
-    lexer.add_rule(r"type", |_| LexResult::Token(Token::TypeKwd));
-    lexer.add_rule(r"tab", |_| LexResult::Token(Token::TableKwd));
-    lexer.add_rule(r"table", |_| LexResult::Token(Token::TableKwd));
-    lexer.add_rule(r"schema", |_| LexResult::Token(Token::SchemaKwd));
-    lexer.add_rule(r"sch", |_| LexResult::Token(Token::SchemaKwd));
+    // kwds and boolean literals (added before idents so they aren't lexed as
+    // such)
+    for (keyword, token) in KEYWORDS {
+        add_rule!(keyword, |_| LexResult::Token(token.clone()));
+    }
 
     // idents
-    lexer.add_rule(r"[a-zA-Z][a-zA-Z0-9_]*", |re_match| {
+    add_rule!(r"[a-zA-Z][a-zA-Z0-9_]*", |re_match| {
         LexResult::Token(Token::Ident(re_match.as_str().into()))
     });
 
     // literals
-    lexer.add_rule(r"\-?[0-9]+", |re_match| {
-        match re_match.as_str().parse::<i32>() {
-            Ok(v) => LexResult::Token(Token::Literal(Literal::Int(v))),
-            Err(e) => LexResult::Error(e.into()),
+    // integers that don't fit in an i32 are lexed as longs
+    add_rule!(r"\-?[0-9]+", |re_match| {
+        let s = re_match.as_str();
+        match (s.parse::<i32>(), s.parse::<i64>()) {
+            (Ok(v), _) => LexResult::Token(Token::Literal(Literal::Int(v))),
+            (_, Ok(v)) => LexResult::Token(Token::Literal(Literal::Long(v))),
+            (_, Err(e)) => LexResult::Error(e.into()),
         }
     });
-    lexer.add_rule(r"\-?[0-9]+(\.[0-9]+)?", |re_match| {
-        match re_match.as_str().parse::<f64>() {
-            Ok(v) => LexResult::Token(Token::Literal(Literal::Dbl(v))),
+    // fractional numbers are lexed as exact decimals, and converted to doubles
+    // where needed
+    add_rule!(r"\-?[0-9]+(\.[0-9]+)?", |re_match| match Decimal::from_str(
+        re_match.as_str()
+    ) {
+        Ok(v) => LexResult::Token(Token::Literal(Literal::Dec(v))),
+        Err(e) => LexResult::Error(e.into()),
+    },);
+    add_rule!(r"[0-9]{4}-[0-9]{2}-[0-9]{2}", |re_match| {
+        match NaiveDate::parse_from_str(re_match.as_str(), DATE_FORMAT) {
+            Ok(v) => LexResult::Token(Token::Literal(Literal::Date(v))),
             Err(e) => LexResult::Error(e.into()),
         }
     });
-    lexer.add_rule("\"[^\"]*\"", |re_match| {
+    add_rule!(r"[0-9]{2}:[0-9]{2}(:[0-9]{2})?", |re_match| match re_match
+        .as_str()
+        .parse::<NaiveTime>()
+    {
+        Ok(v) => LexResult::Token(Token::Literal(Literal::Time(v))),
+        Err(e) => LexResult::Error(e.into()),
+    });
+    add_rule!(
+        r"[0-9]{4}-[0-9]{2}-[0-9]{2}T[0-9]{2}:[0-9]{2}:[0-9]{2}",
+        |re_match| match re_match.as_str().parse::<NaiveDateTime>() {
+            Ok(v) => LexResult::Token(Token::Literal(Literal::DateTime(v))),
+            Err(e) => LexResult::Error(e.into()),
+        },
+    );
+    add_rule!("\"[^\"]*\"", |re_match| {
         LexResult::Token(Token::Literal(Literal::Str(
             re_match.as_str()[1..re_match.len() - 1].into(),
         )))
     });
 
+    // anything else is an error, added last so that it only matches where
+    // no other rule does. Unterminated strings run to the end of the line.
+    add_rule!("\"[^\"\n]*", |_| {
+        LexResult::Error(anyhow::anyhow!("unterminated string"))
+    });
+    add_rule!("(?s:.)", |_| {
+        LexResult::Error(anyhow::anyhow!("unexpected character"))
+    });
+
     lexer
 }
@@ -1,5 +1,7 @@
+pub mod diagnostic;
 pub mod lex;
 pub mod parse;
+pub mod span;
 
 use std::{collections::HashMap, rc::Rc};
 
@@ -1,12 +1,87 @@
 use std::collections::HashMap;
 use std::rc::Rc;
 
+use crate::core::schema::check::{BinaryOp, Expr, UnaryOp};
 use crate::core::schema::{
-    ColumnSchema, DBL_TYPE_NAME, DataType, DblDataType, INT_TYPE_NAME,
-    IntDataType, STR_TYPE_NAME, SpreadsheetSchema, StrDataType, TableSchema,
+    BOOL_TYPE_NAME, BoolDataType, ColumnSchema, DATE_TYPE_NAME,
+    DATETIME_TYPE_NAME, DBL_TYPE_NAME, DEC_TYPE_NAME, DataType, DateDataType,
+    DateTimeDataType, DblDataType, DecDataType, ENUM_TYPE_NAME, EnumDataType,
+    ForeignKey, INT_TYPE_NAME, IntDataType, LONG_TYPE_NAME, LongDataType,
+    STR_TYPE_NAME, SpreadsheetSchema, StrDataType, TIME_TYPE_NAME, TableSchema,
+    TimeDataType,
 };
-use crate::ql::{Stmt, Symbol, SymbolTable, lex::Token};
+use crate::error::SchemaError;
+use crate::ql::{
+    Stmt, Symbol, SymbolTable,
+    lex::{Literal, Token},
+    span::TokenError,
+};
+use regex::Regex;
 use rlrl::parse::{ParseResult, TokenQueue};
+
+// Builds an error at the token at `idx`.
+fn error_at_idx(idx: usize, error: SchemaError) -> anyhow::Error {
+    anyhow::Error::new(TokenError::new(idx, error))
+}
+
+// Builds an error at the token `tq` is up to.
+fn error_at(tq: &TokenQueue<Token>, error: SchemaError) -> anyhow::Error {
+    error_at_idx(tq.get_idx(), error)
+}
+
+// Builds an error at the token `tq` consumed last.
+fn error_at_last(tq: &TokenQueue<Token>, error: SchemaError) -> anyhow::Error {
+    error_at_idx(tq.get_idx().saturating_sub(1), error)
+}
+
+// Builds an error for `token` when the parser expected something else.
+fn unexpected_token(expected: &str, token: Option<&Token>) -> SchemaError {
+    SchemaError::UnexpectedToken {
+        expected: expected.into(),
+        found: token.map(|tok| format!("{tok:?}")),
+    }
+}
+
+// Builds an error at the token `tq` is up to, which isn't `expected`.
+fn unexpected(tq: &TokenQueue<Token>, expected: &str) -> anyhow::Error {
+    let found = tq.clone().consume().ok();
+    error_at(tq, unexpected_token(expected, found.as_ref()))
+}
+
+// Whether `tok` is the word `word`. Constraint keywords and the operators in
+// checks are lexed as identifiers, so that they can still be used as names.
+fn is_word(tok: &Token, word: &str) -> bool {
+    matches!(tok, Token::Ident(ident) if &**ident == word)
+}
+
+// Consumes the word `word` if it's next.
+fn consume_word(tq: &mut TokenQueue<Token>, word: &str) -> bool {
+    tq.consume_matching(|tok| is_word(tok, word)).is_ok()
+}
+
+// Consumes `token`, failing at the current token if it's something else.
+fn expect(
+    tq: &mut TokenQueue<Token>,
+    token: Token,
+    what: &str,
+) -> anyhow::Result<()> {
+    match tq.consume_eq(token) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(unexpected(tq, what)),
+    }
+}
+
+// Consumes an identifier or string literal naming `what`.
+fn consume_name(
+    tq: &mut TokenQueue<Token>,
+    what: &str,
+) -> anyhow::Result<Rc<str>> {
+    tq.consume_matching(|tok| tok.is_ident_or_str_literal_tok())
+        .ok()
+        .and_then(|tok| tok.get_ident_or_str_literal())
+        .ok_or_else(|| unexpected(tq, what))
+}
+
 pub trait Parse: Sized {
     fn parse(
         tq: &TokenQueue<Token>,
@@ -34,15 +109,19 @@ impl Parse for IntDataType {
                     if literal.is_i32() {
                         Some(literal.get_i32().unwrap())
                     } else {
-                        return Err(anyhow::anyhow!(
-                            "Couldn't parse int literal!"
+                        return Err(error_at_last(
+                            &tq,
+                            SchemaError::InvalidLiteral {
+                                expected: "int".into(),
+                                found: literal.to_string(),
+                            },
                         ));
                     }
                 }
                 Err(_) => None,
             };
 
-            tq.consume_eq(Token::Comma)?;
+            expect(&mut tq, Token::Comma, "','")?;
 
             // consume max
             let max = match tq.clone().peek_matching(|token| token.is_literal())
@@ -53,15 +132,19 @@ impl Parse for IntDataType {
                     if literal.is_i32() {
                         Some(literal.get_i32().unwrap())
                     } else {
-                        return Err(anyhow::anyhow!(
-                            "Couldn't parse int literal!"
+                        return Err(error_at_last(
+                            &tq,
+                            SchemaError::InvalidLiteral {
+                                expected: "int".into(),
+                                found: literal.to_string(),
+                            },
                         ));
                     }
                 }
                 Err(_) => None,
             };
 
-            tq.consume_eq(Token::CAngle)?;
+            expect(&mut tq, Token::CAngle, "'>'")?;
 
             (min, max)
         };
@@ -74,6 +157,25 @@ impl Parse for IntDataType {
     }
 }
 
+impl Parse for LongDataType {
+    fn parse(
+        tq: &TokenQueue<Token>,
+        _symtable: &mut SymbolTable,
+    ) -> ParseResult<Self> {
+        // create a mutable copy
+        let mut tq = tq.clone();
+
+        let (min, max) =
+            parse_bounds(&mut tq, Literal::get_i64, LONG_TYPE_NAME)?;
+
+        // consume ?
+        let nullable = tq.consume_eq(Token::QMark).is_ok();
+
+        // done
+        Ok((LongDataType::new(min, max, nullable), tq.get_idx()))
+    }
+}
+
 impl Parse for DblDataType {
     fn parse(
         tq: &TokenQueue<Token>,
@@ -93,15 +195,19 @@ impl Parse for DblDataType {
                     if literal.is_f64() {
                         Some(literal.get_f64().unwrap())
                     } else {
-                        return Err(anyhow::anyhow!(
-                            "Couldn't parse dbl literal!"
+                        return Err(error_at_last(
+                            &tq,
+                            SchemaError::InvalidLiteral {
+                                expected: "dbl".into(),
+                                found: literal.to_string(),
+                            },
                         ));
                     }
                 }
                 Err(_) => None,
             };
 
-            tq.consume_eq(Token::Comma)?;
+            expect(&mut tq, Token::Comma, "','")?;
 
             // consume max
             let max = match tq.clone().peek_matching(|token| token.is_literal())
@@ -112,15 +218,19 @@ impl Parse for DblDataType {
                     if literal.is_f64() {
                         Some(literal.get_f64().unwrap())
                     } else {
-                        return Err(anyhow::anyhow!(
-                            "Couldn't parse dbl literal!"
+                        return Err(error_at_last(
+                            &tq,
+                            SchemaError::InvalidLiteral {
+                                expected: "dbl".into(),
+                                found: literal.to_string(),
+                            },
                         ));
                     }
                 }
                 Err(_) => None,
             };
 
-            tq.consume_eq(Token::CAngle)?;
+            expect(&mut tq, Token::CAngle, "'>'")?;
 
             (min, max)
         };
@@ -153,16 +263,22 @@ impl Parse for StrDataType {
                     Some(
                         literal
                             .get_i32()
-                            .ok_or(anyhow::anyhow!(
-                                "Couldn't get size literal!"
-                            ))?
-                            .try_into()?,
+                            .and_then(|size| size.try_into().ok())
+                            .ok_or_else(|| {
+                                error_at_last(
+                                    &tq,
+                                    SchemaError::InvalidLiteral {
+                                        expected: "size".into(),
+                                        found: literal.to_string(),
+                                    },
+                                )
+                            })?,
                     )
                 }
                 Err(_) => None,
             };
 
-            tq.consume_eq(Token::Comma)?;
+            expect(&mut tq, Token::Comma, "','")?;
 
             // consume max
             let max = match tq.clone().peek_matching(|token| token.is_literal())
@@ -173,25 +289,242 @@ impl Parse for StrDataType {
                     Some(
                         literal
                             .get_i32()
-                            .ok_or(anyhow::anyhow!(
-                                "Couldn't get size literal!"
-                            ))?
-                            .try_into()?,
+                            .and_then(|size| size.try_into().ok())
+                            .ok_or_else(|| {
+                                error_at_last(
+                                    &tq,
+                                    SchemaError::InvalidLiteral {
+                                        expected: "size".into(),
+                                        found: literal.to_string(),
+                                    },
+                                )
+                            })?,
                     )
                 }
                 Err(_) => None,
             };
 
-            tq.consume_eq(Token::CAngle)?;
+            expect(&mut tq, Token::CAngle, "'>'")?;
 
             (min, max)
         };
 
+        // consume ~ "pattern"
+        let pattern = match tq.consume_eq(Token::Tilde) {
+            Ok(_) => {
+                let pattern = tq
+                    .consume_matching(|tok| {
+                        tok.get_literal().is_some_and(Literal::is_str)
+                    })
+                    .ok()
+                    .and_then(|tok| {
+                        tok.get_literal().and_then(Literal::get_str)
+                    })
+                    .ok_or_else(|| unexpected(&tq, "pattern literal"))?;
+                Some(Regex::new(&pattern).map_err(|e| {
+                    error_at_last(
+                        &tq,
+                        SchemaError::InvalidPattern {
+                            pattern: pattern.to_string(),
+                            message: e.to_string(),
+                        },
+                    )
+                })?)
+            }
+            Err(_) => None,
+        };
+
+        // consume ?
+        let nullable = tq.consume_eq(Token::QMark).is_ok();
+
+        // done
+        Ok((StrDataType::new(min, max, pattern, nullable), tq.get_idx()))
+    }
+}
+
+impl Parse for DecDataType {
+    fn parse(
+        tq: &TokenQueue<Token>,
+        _symtable: &mut SymbolTable,
+    ) -> ParseResult<Self> {
+        // create a mutable copy
+        let mut tq = tq.clone();
+
+        // precision and scale are both required
+        let consume_size = |tq: &mut TokenQueue<Token>| {
+            tq.consume_matching(|tok| tok.is_literal())
+                .ok()
+                .and_then(|tok| tok.get_literal().and_then(Literal::get_i32))
+                .and_then(|size| u32::try_from(size).ok())
+                .ok_or_else(|| unexpected(tq, "size literal"))
+        };
+
+        if tq.consume_eq(Token::OAngle).is_err() {
+            return Err(unexpected(&tq, "dec<precision, scale>"));
+        }
+        let precision = consume_size(&mut tq)?;
+        expect(&mut tq, Token::Comma, "','")?;
+        let scale = consume_size(&mut tq)?;
+        expect(&mut tq, Token::CAngle, "'>'")?;
+
+        // consume ?
+        let nullable = tq.consume_eq(Token::QMark).is_ok();
+
+        // done
+        Ok((DecDataType::new(precision, scale, nullable), tq.get_idx()))
+    }
+}
+
+impl Parse for BoolDataType {
+    fn parse(
+        tq: &TokenQueue<Token>,
+        _symtable: &mut SymbolTable,
+    ) -> ParseResult<Self> {
+        // create a mutable copy
+        let mut tq = tq.clone();
+
+        // consume ?
+        let nullable = tq.consume_eq(Token::QMark).is_ok();
+
+        // done
+        Ok((BoolDataType::new(nullable), tq.get_idx()))
+    }
+}
+
+/// Consumes a single optional bound inside `<min, max>`, converting the
+/// literal with `get`.
+fn parse_bound<T>(
+    tq: &mut TokenQueue<Token>,
+    get: fn(&Literal) -> Option<T>,
+    type_name: &str,
+) -> anyhow::Result<Option<T>> {
+    match tq.clone().peek_matching(|token| token.is_literal()) {
+        Ok(token) => {
+            tq.increment()?;
+            let literal = token.get_literal().unwrap();
+            match get(literal) {
+                Some(val) => Ok(Some(val)),
+                None => Err(error_at_last(
+                    tq,
+                    SchemaError::InvalidLiteral {
+                        expected: type_name.into(),
+                        found: literal.to_string(),
+                    },
+                )),
+            }
+        }
+        Err(_) => Ok(None),
+    }
+}
+
+/// Consumes optional `<min, max>` bounds, converting each literal with `get`.
+fn parse_bounds<T>(
+    tq: &mut TokenQueue<Token>,
+    get: fn(&Literal) -> Option<T>,
+    type_name: &str,
+) -> anyhow::Result<(Option<T>, Option<T>)> {
+    if tq.consume_eq(Token::OAngle).is_err() {
+        return Ok((None, None));
+    }
+
+    // consume min
+    let min = parse_bound(tq, get, type_name)?;
+
+    expect(tq, Token::Comma, "','")?;
+
+    // consume max
+    let max = parse_bound(tq, get, type_name)?;
+
+    expect(tq, Token::CAngle, "'>'")?;
+
+    Ok((min, max))
+}
+
+impl Parse for DateDataType {
+    fn parse(
+        tq: &TokenQueue<Token>,
+        _symtable: &mut SymbolTable,
+    ) -> ParseResult<Self> {
+        // create a mutable copy
+        let mut tq = tq.clone();
+
+        let (min, max) =
+            parse_bounds(&mut tq, Literal::get_date, DATE_TYPE_NAME)?;
+
+        // consume ?
+        let nullable = tq.consume_eq(Token::QMark).is_ok();
+
+        // done
+        Ok((DateDataType::new(min, max, nullable), tq.get_idx()))
+    }
+}
+
+impl Parse for TimeDataType {
+    fn parse(
+        tq: &TokenQueue<Token>,
+        _symtable: &mut SymbolTable,
+    ) -> ParseResult<Self> {
+        // create a mutable copy
+        let mut tq = tq.clone();
+
+        let (min, max) =
+            parse_bounds(&mut tq, Literal::get_time, TIME_TYPE_NAME)?;
+
         // consume ?
         let nullable = tq.consume_eq(Token::QMark).is_ok();
 
         // done
-        Ok((StrDataType::new(min, max, nullable), tq.get_idx()))
+        Ok((TimeDataType::new(min, max, nullable), tq.get_idx()))
+    }
+}
+
+impl Parse for DateTimeDataType {
+    fn parse(
+        tq: &TokenQueue<Token>,
+        _symtable: &mut SymbolTable,
+    ) -> ParseResult<Self> {
+        // create a mutable copy
+        let mut tq = tq.clone();
+
+        let (min, max) =
+            parse_bounds(&mut tq, Literal::get_datetime, DATETIME_TYPE_NAME)?;
+
+        // consume ?
+        let nullable = tq.consume_eq(Token::QMark).is_ok();
+
+        // done
+        Ok((DateTimeDataType::new(min, max, nullable), tq.get_idx()))
+    }
+}
+
+impl Parse for EnumDataType {
+    fn parse(
+        tq: &TokenQueue<Token>,
+        _symtable: &mut SymbolTable,
+    ) -> ParseResult<Self> {
+        // create a mutable copy
+        let mut tq = tq.clone();
+
+        expect(&mut tq, Token::OParen, "'('")?;
+
+        // consume variants
+        let mut variants = Vec::new();
+        while let Ok(token) = tq.consume_matching(|token| {
+            token.get_literal().is_some_and(|literal| literal.is_str())
+        }) {
+            variants.push(token.get_literal().unwrap().get_str().unwrap());
+            if tq.consume_eq(Token::Comma).is_err() {
+                break;
+            }
+        }
+
+        expect(&mut tq, Token::CParen, "')'")?;
+
+        // consume ?
+        let nullable = tq.consume_eq(Token::QMark).is_ok();
+
+        // done
+        Ok((EnumDataType::new(variants, nullable), tq.get_idx()))
     }
 }
 
@@ -201,38 +534,95 @@ fn parse_data_type(
 ) -> ParseResult<Rc<dyn DataType>> {
     let mut tq = tq.clone();
 
-    let ident_tok =
-        tq.consume_matching(|tok| tok.is_ident_or_str_literal_tok())?;
-    let ident = ident_tok
-        .get_ident_or_str_literal()
-        .ok_or(anyhow::anyhow!("Expected an identifier!"))?;
+    let ident = consume_name(&mut tq, "type name")?;
 
     match &ident as &str {
         INT_TYPE_NAME => {
             let (dtype, end) = IntDataType::parse(&tq, symtable)?;
             return Ok((Rc::new(dtype), end));
         }
+        LONG_TYPE_NAME => {
+            let (dtype, end) = LongDataType::parse(&tq, symtable)?;
+            return Ok((Rc::new(dtype), end));
+        }
         DBL_TYPE_NAME => {
             let (dtype, end) = DblDataType::parse(&tq, symtable)?;
             return Ok((Rc::new(dtype), end));
         }
+        DEC_TYPE_NAME => {
+            let (dtype, end) = DecDataType::parse(&tq, symtable)?;
+            return Ok((Rc::new(dtype), end));
+        }
         STR_TYPE_NAME => {
             let (dtype, end) = StrDataType::parse(&tq, symtable)?;
             return Ok((Rc::new(dtype), end));
         }
+        BOOL_TYPE_NAME => {
+            let (dtype, end) = BoolDataType::parse(&tq, symtable)?;
+            return Ok((Rc::new(dtype), end));
+        }
+        DATE_TYPE_NAME => {
+            let (dtype, end) = DateDataType::parse(&tq, symtable)?;
+            return Ok((Rc::new(dtype), end));
+        }
+        TIME_TYPE_NAME => {
+            let (dtype, end) = TimeDataType::parse(&tq, symtable)?;
+            return Ok((Rc::new(dtype), end));
+        }
+        DATETIME_TYPE_NAME => {
+            let (dtype, end) = DateTimeDataType::parse(&tq, symtable)?;
+            return Ok((Rc::new(dtype), end));
+        }
+        ENUM_TYPE_NAME => {
+            let (dtype, end) = EnumDataType::parse(&tq, symtable)?;
+            return Ok((Rc::new(dtype), end));
+        }
         _ => {
             if let Some(Symbol::DataType(dtype)) = symtable.get(&ident) {
                 return Ok((dtype.clone(), tq.get_idx()));
             } else {
-                return Err(anyhow::anyhow!(
-                    "Unrecognised type name {}",
-                    ident
+                return Err(error_at_last(
+                    &tq,
+                    SchemaError::UnknownType {
+                        name: ident.to_string(),
+                    },
                 ));
             }
         }
     }
 }
 
+/// Parses the `Table(column)` target of a `references` clause.
+fn parse_foreign_key(
+    tq: &TokenQueue<Token>,
+    _symtable: &mut SymbolTable,
+) -> ParseResult<ForeignKey> {
+    let mut tq = tq.clone();
+
+    let table = consume_name(&mut tq, "referenced table name")?;
+
+    expect(&mut tq, Token::OParen, "'('")?;
+
+    let column = consume_name(&mut tq, "referenced column name")?;
+
+    expect(&mut tq, Token::CParen, "')'")?;
+
+    Ok((ForeignKey::new(table, column), tq.get_idx()))
+}
+
+// Converts a numeric literal to the literal type stored by the column type
+// called `type_name`, since e.g. every fractional literal lexes as a `Dec`.
+// Other literals are returned unchanged, to be validated against the type.
+fn convert_literal(lit: Literal, type_name: &str) -> Literal {
+    let converted = match type_name {
+        LONG_TYPE_NAME => lit.get_i64().map(Literal::Long),
+        DBL_TYPE_NAME => lit.get_f64().map(Literal::Dbl),
+        DEC_TYPE_NAME => lit.get_dec().map(Literal::Dec),
+        _ => None,
+    };
+    converted.unwrap_or(lit)
+}
+
 type ColumnSchemaDef = (ColumnSchema, Rc<str>);
 
 impl Parse for ColumnSchemaDef {
@@ -242,33 +632,303 @@ impl Parse for ColumnSchemaDef {
     ) -> ParseResult<Self> {
         let mut tq: TokenQueue<Token> = tq.clone();
 
-        let column_name = tq
-            .consume_matching(|tok| tok.is_ident_or_str_literal_tok())?
-            .get_ident_or_str_literal()
-            .ok_or(anyhow::anyhow!("Couldn't get column name!"))?
-            .into();
+        let column_name = consume_name(&mut tq, "column name")?;
 
-        tq.consume_eq(Token::Colon)?;
+        expect(&mut tq, Token::Colon, "':'")?;
 
+        // a named type is recorded so that it can be referred to by name
+        let type_name = tq
+            .peek_matching(|tok| tok.is_ident_or_str_literal_tok())
+            .ok()
+            .and_then(|tok| tok.get_ident_or_str_literal());
         let column_type = tq.parse_with_mut(parse_data_type, symtable)?;
+        let type_alias = type_name.filter(|name| {
+            matches!(
+                symtable.get(name),
+                Some(Symbol::DataType(dtype)) if Rc::ptr_eq(dtype, &column_type)
+            )
+        });
 
         let default_value = match tq.consume_eq(Token::Equals) {
             Ok(_) => Some(
-                tq.consume()?
-                    .get_literal()
-                    .ok_or(anyhow::anyhow!("Couldn't get default value!"))?
-                    .clone(),
+                tq.consume_matching(|tok| tok.is_literal())
+                    .ok()
+                    .and_then(|tok| tok.get_literal().cloned())
+                    .map(|lit| {
+                        convert_literal(lit, column_type.get_type_name())
+                    })
+                    .ok_or_else(|| unexpected(&tq, "default value"))?,
             ),
             Err(_) => None,
         };
 
+        let references = match tq.consume_eq(Token::ReferencesKwd) {
+            Ok(_) => Some(tq.parse_with_mut(parse_foreign_key, symtable)?),
+            Err(_) => None,
+        };
+
         Ok((
-            (ColumnSchema::new(column_type, default_value), column_name),
+            (
+                ColumnSchema::new(
+                    column_type,
+                    type_alias,
+                    default_value,
+                    references,
+                ),
+                column_name,
+            ),
             tq.get_idx(),
         ))
     }
 }
 
+/// Parses a parenthesised, comma-separated list of column names.
+fn parse_column_list(
+    tq: &TokenQueue<Token>,
+    _symtable: &mut SymbolTable,
+) -> ParseResult<Vec<Rc<str>>> {
+    let mut tq = tq.clone();
+
+    expect(&mut tq, Token::OParen, "'('")?;
+
+    let mut column_names = Vec::new();
+    while let Ok(tok) =
+        tq.consume_matching(|tok| tok.is_ident_or_str_literal_tok())
+    {
+        column_names.push(tok.get_ident_or_str_literal().unwrap());
+        if tq.consume_eq(Token::Comma).is_err() {
+            break;
+        }
+    }
+
+    expect(&mut tq, Token::CParen, "')'")?;
+
+    Ok((column_names, tq.get_idx()))
+}
+
+/// Parses a `primary key (col, ...)` table constraint. The `primary` keyword
+/// is optional.
+fn parse_primary_key(
+    tq: &TokenQueue<Token>,
+    symtable: &mut SymbolTable,
+) -> ParseResult<Vec<Rc<str>>> {
+    let mut tq = tq.clone();
+
+    consume_word(&mut tq, "primary");
+    if !consume_word(&mut tq, "key") {
+        return Err(unexpected(&tq, "'key'"));
+    }
+    let key = tq.parse_with_mut(parse_column_list, symtable)?;
+
+    Ok((key, tq.get_idx()))
+}
+
+// Negates a numeric literal, widening ints that overflow.
+fn negate_literal(literal: &Literal) -> Option<Literal> {
+    match literal {
+        Literal::Int(val) => Some(match val.checked_neg() {
+            Some(val) => Literal::Int(val),
+            None => Literal::Long(-(*val as i64)),
+        }),
+        Literal::Long(val) => val.checked_neg().map(Literal::Long),
+        Literal::Dbl(val) => Some(Literal::Dbl(-val)),
+        Literal::Dec(val) => Some(Literal::Dec(-val)),
+        _ => None,
+    }
+}
+
+// Words that are operators in checks, so can't name a column in one.
+const EXPR_WORDS: [&str; 5] = ["and", "or", "not", "is", "null"];
+
+fn parse_primary_expr(tq: &mut TokenQueue<Token>) -> anyhow::Result<Expr> {
+    let token = tq.consume().map_err(|_| unexpected(tq, "an expression"))?;
+    match token {
+        Token::OParen => {
+            let expr = parse_or_expr(tq)?;
+            expect(tq, Token::CParen, "')'")?;
+            Ok(expr)
+        }
+        Token::Ident(name) if !EXPR_WORDS.contains(&&*name) => {
+            Ok(Expr::Column(name))
+        }
+        Token::Literal(literal) => Ok(Expr::Literal(literal)),
+        token => Err(error_at_last(
+            tq,
+            unexpected_token("an expression", Some(&token)),
+        )),
+    }
+}
+
+fn parse_unary_expr(tq: &mut TokenQueue<Token>) -> anyhow::Result<Expr> {
+    if tq.consume_eq(Token::Minus).is_ok() {
+        let expr = parse_unary_expr(tq)?;
+        return Ok(Expr::Unary(UnaryOp::Neg, Box::new(expr)));
+    }
+    parse_primary_expr(tq)
+}
+
+// Parses any `* operand` or `/ operand` following `lhs`.
+fn parse_mul_rest(
+    tq: &mut TokenQueue<Token>,
+    mut lhs: Expr,
+) -> anyhow::Result<Expr> {
+    loop {
+        let op = if tq.consume_eq(Token::Star).is_ok() {
+            BinaryOp::Mul
+        } else if tq.consume_eq(Token::Slash).is_ok() {
+            BinaryOp::Div
+        } else {
+            return Ok(lhs);
+        };
+        let rhs = parse_unary_expr(tq)?;
+        lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+    }
+}
+
+fn parse_add_expr(tq: &mut TokenQueue<Token>) -> anyhow::Result<Expr> {
+    let lhs = parse_unary_expr(tq)?;
+    let mut lhs = parse_mul_rest(tq, lhs)?;
+    loop {
+        let (op, rhs) = if tq.consume_eq(Token::Plus).is_ok() {
+            (BinaryOp::Add, parse_unary_expr(tq)?)
+        } else if tq.consume_eq(Token::Minus).is_ok() {
+            (BinaryOp::Sub, parse_unary_expr(tq)?)
+        } else if let Ok(token) = tq.clone().peek_matching(|tok| {
+            tok.get_literal()
+                .and_then(|literal| literal.get_f64())
+                .is_some_and(|val| val.is_sign_negative())
+        }) {
+            // `a-1` is lexed as `a` followed by the literal `-1`
+            tq.increment()?;
+            let literal = token.get_literal().unwrap();
+            let literal = negate_literal(literal).ok_or_else(|| {
+                error_at_last(
+                    tq,
+                    SchemaError::InvalidLiteral {
+                        expected: "negated".into(),
+                        found: literal.to_string(),
+                    },
+                )
+            })?;
+            (BinaryOp::Sub, Expr::Literal(literal))
+        } else {
+            return Ok(lhs);
+        };
+        let rhs = parse_mul_rest(tq, rhs)?;
+        lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+    }
+}
+
+fn parse_comparison_op(tq: &mut TokenQueue<Token>) -> Option<BinaryOp> {
+    if tq.consume_eq(Token::Equals).is_ok() {
+        Some(BinaryOp::Eq)
+    } else if tq.consume_eq(Token::NotEquals).is_ok() {
+        Some(BinaryOp::Ne)
+    } else if tq.consume_eq(Token::OAngle).is_ok() {
+        // `<=` is lexed as two tokens so it doesn't clash with type bounds
+        match tq.consume_eq(Token::Equals) {
+            Ok(_) => Some(BinaryOp::Le),
+            Err(_) => Some(BinaryOp::Lt),
+        }
+    } else if tq.consume_eq(Token::CAngle).is_ok() {
+        match tq.consume_eq(Token::Equals) {
+            Ok(_) => Some(BinaryOp::Ge),
+            Err(_) => Some(BinaryOp::Gt),
+        }
+    } else {
+        None
+    }
+}
+
+fn parse_comparison_expr(tq: &mut TokenQueue<Token>) -> anyhow::Result<Expr> {
+    let mut expr = parse_add_expr(tq)?;
+
+    if let Some(op) = parse_comparison_op(tq) {
+        let rhs = parse_add_expr(tq)?;
+        expr = Expr::Binary(op, Box::new(expr), Box::new(rhs));
+    }
+
+    // consume `is [not] null`
+    if consume_word(tq, "is") {
+        let negated = consume_word(tq, "not");
+        if !consume_word(tq, "null") {
+            return Err(unexpected(tq, "'null'"));
+        }
+        expr = Expr::IsNull(Box::new(expr), negated);
+    }
+
+    Ok(expr)
+}
+
+fn parse_not_expr(tq: &mut TokenQueue<Token>) -> anyhow::Result<Expr> {
+    if consume_word(tq, "not") {
+        let expr = parse_not_expr(tq)?;
+        return Ok(Expr::Unary(UnaryOp::Not, Box::new(expr)));
+    }
+    parse_comparison_expr(tq)
+}
+
+fn parse_and_expr(tq: &mut TokenQueue<Token>) -> anyhow::Result<Expr> {
+    let mut expr = parse_not_expr(tq)?;
+    while consume_word(tq, "and") {
+        let rhs = parse_not_expr(tq)?;
+        expr = Expr::Binary(BinaryOp::And, Box::new(expr), Box::new(rhs));
+    }
+    Ok(expr)
+}
+
+fn parse_or_expr(tq: &mut TokenQueue<Token>) -> anyhow::Result<Expr> {
+    let mut expr = parse_and_expr(tq)?;
+    while consume_word(tq, "or") {
+        let rhs = parse_and_expr(tq)?;
+        expr = Expr::Binary(BinaryOp::Or, Box::new(expr), Box::new(rhs));
+    }
+    Ok(expr)
+}
+
+impl Parse for Expr {
+    fn parse(
+        tq: &TokenQueue<Token>,
+        _symtable: &mut SymbolTable,
+    ) -> ParseResult<Self> {
+        // create a mutable copy
+        let mut tq = tq.clone();
+
+        let expr = parse_or_expr(&mut tq)?;
+
+        // done
+        Ok((expr, tq.get_idx()))
+    }
+}
+
+/// Parses a `check <expr>` table constraint.
+fn parse_check(
+    tq: &TokenQueue<Token>,
+    symtable: &mut SymbolTable,
+) -> ParseResult<Expr> {
+    let mut tq = tq.clone();
+
+    if !consume_word(&mut tq, "check") {
+        return Err(unexpected(&tq, "'check'"));
+    }
+    let expr = tq.parse_with_mut(Expr::parse, symtable)?;
+
+    Ok((expr, tq.get_idx()))
+}
+
+/// Parses a `unique (col, ...)` table constraint.
+fn parse_unique_constraint(
+    tq: &TokenQueue<Token>,
+    symtable: &mut SymbolTable,
+) -> ParseResult<Vec<Rc<str>>> {
+    let mut tq = tq.clone();
+
+    tq.consume_eq(Token::UniqueKwd)?;
+    let columns = tq.parse_with_mut(parse_column_list, symtable)?;
+
+    Ok((columns, tq.get_idx()))
+}
+
 type TableSchemaDef = (TableSchema, Rc<str>);
 
 impl Parse for TableSchemaDef {
@@ -278,39 +938,85 @@ impl Parse for TableSchemaDef {
     ) -> ParseResult<Self> {
         let mut tq = tq.clone();
 
-        let table_name = tq
-            .consume()?
-            .get_ident_or_str_literal()
-            .ok_or(anyhow::anyhow!("Couldn't get table name!"))?;
+        let table_name = consume_name(&mut tq, "table name")?;
 
-        tq.consume_eq(Token::OParen)
-            .map_err(|_| anyhow::anyhow!("Couldn't get '('"))?;
+        expect(&mut tq, Token::OParen, "'('")?;
 
         let mut columns = HashMap::new();
         let mut column_names = Vec::new();
+        let mut primary_key = None;
+        let mut unique = Vec::new();
+        let mut checks = Vec::new();
 
-        while let Ok((column, column_name)) =
-            tq.parse_with_mut(ColumnSchemaDef::parse, symtable)
-        {
-            match columns.insert(column_name.clone(), column) {
-                Some(_) => {
-                    return Err(anyhow::anyhow!(
-                        "Can't have multiple columns named '{column_name}'"
+        // each item is told apart by its first tokens, so that errors inside
+        // it are reported rather than skipped over. Constraints start with
+        // words that can also name a column, which is always followed by a
+        // `:`.
+        let starts_with = |tq: &TokenQueue<Token>, tokens: &[Token]| {
+            tq.peek_matching(|tok| tokens.contains(tok)).is_ok()
+        };
+        let starts_with_word = |tq: &TokenQueue<Token>, word: &str| {
+            let mut tq = tq.clone();
+            consume_word(&mut tq, word) && !starts_with(&tq, &[Token::Colon])
+        };
+        loop {
+            let start = tq.clone();
+            if starts_with(&tq, &[Token::CParen]) {
+                break;
+            } else if starts_with_word(&tq, "primary")
+                || starts_with_word(&tq, "key")
+            {
+                let key = tq.parse_with_mut(parse_primary_key, symtable)?;
+                if primary_key.replace(key).is_some() {
+                    return Err(error_at(
+                        &start,
+                        SchemaError::DuplicatePrimaryKey {
+                            table: table_name.to_string(),
+                        },
+                    ));
+                }
+            } else if starts_with(&tq, &[Token::UniqueKwd]) {
+                unique.push(
+                    tq.parse_with_mut(parse_unique_constraint, symtable)?,
+                );
+            } else if starts_with_word(&tq, "check") {
+                checks.push(tq.parse_with_mut(parse_check, symtable)?);
+            } else {
+                let (column, column_name) =
+                    tq.parse_with_mut(ColumnSchemaDef::parse, symtable)?;
+                if columns.insert(column_name.clone(), column).is_some() {
+                    return Err(error_at(
+                        &start,
+                        SchemaError::DuplicateColumn {
+                            table: table_name.to_string(),
+                            column: column_name.to_string(),
+                        },
                     ));
                 }
-                None => {}
+                // a trailing `unique` is shorthand for `unique (column)`
+                if tq.consume_eq(Token::UniqueKwd).is_ok() {
+                    unique.push(vec![column_name.clone()]);
+                }
+                column_names.push(column_name);
             }
-            column_names.push(column_name);
             if tq.consume_eq(Token::Comma).is_err() {
                 break;
             }
         }
 
-        tq.consume_eq(Token::CParen)
-            .map_err(|_| anyhow::anyhow!("Couldn't get ')'"))?;
+        expect(&mut tq, Token::CParen, "')'")?;
 
         Ok((
-            (TableSchema::new(columns, column_names), table_name),
+            (
+                TableSchema::new(
+                    columns,
+                    column_names,
+                    primary_key,
+                    unique,
+                    checks,
+                ),
+                table_name,
+            ),
             tq.get_idx(),
         ))
     }
@@ -325,11 +1031,8 @@ impl Parse for Stmt {
 
         match tq.consume() {
             Ok(Token::TypeKwd) => {
-                let type_name: Rc<str> = tq
-                    .consume_matching(|tok| tok.is_ident_or_str_literal_tok())?
-                    .get_ident_or_str_literal()
-                    .ok_or(anyhow::anyhow!("Couldn't get type name!"))?
-                    .into();
+                let name_tq = tq.clone();
+                let type_name = consume_name(&mut tq, "type name")?;
 
                 let data_type = tq.parse_with_mut(parse_data_type, symtable)?;
 
@@ -337,15 +1040,18 @@ impl Parse for Stmt {
                     type_name.clone(),
                     Symbol::DataType(data_type.clone()),
                 ) {
-                    return Err(anyhow::anyhow!(
-                        "Symbol {} is already assigned!",
-                        &type_name
+                    return Err(error_at(
+                        &name_tq,
+                        SchemaError::DuplicateSymbol {
+                            name: type_name.to_string(),
+                        },
                     ));
                 }
 
                 Ok((Stmt::TypeDef(type_name, data_type), tq.get_idx()))
             }
             Ok(Token::TableKwd) => {
+                let name_tq = tq.clone();
                 let (schema, schema_name) =
                     tq.parse_with_mut(TableSchemaDef::parse, symtable)?;
                 let schema = Rc::new(schema);
@@ -354,17 +1060,114 @@ impl Parse for Stmt {
                     schema_name.clone(),
                     Symbol::TableSchema(schema.clone()),
                 ) {
-                    return Err(anyhow::anyhow!(
-                        "Symbol {} is already assigned!",
-                        schema_name
+                    return Err(error_at(
+                        &name_tq,
+                        SchemaError::DuplicateSymbol {
+                            name: schema_name.to_string(),
+                        },
                     ));
                 }
                 Ok((Stmt::TableSchema(schema_name, schema), tq.get_idx()))
             }
-            Ok(_) => Err(anyhow::anyhow!("Couldn't parse statement!")),
-            Err(_) => Err(anyhow::anyhow!("Couldn't parse statement!")),
+            Ok(token) => Err(error_at_last(
+                &tq,
+                unexpected_token("'type' or 'table'", Some(&token)),
+            )),
+            Err(_) => Err(unexpected(&tq, "'type' or 'table'")),
+        }
+    }
+}
+
+// Finds where to carry on after the statement at `start` failed with
+// `error`: just past the next `;` from where it failed, or at the next
+// `type` or `table` keyword, whichever comes first.
+fn resync(start: &TokenQueue<Token>, error: &TokenError) -> TokenQueue<Token> {
+    let mut tq = start.clone();
+    while tq.get_idx() < error.get_idx() && tq.increment().is_ok() {}
+    while let Ok(token) = tq.clone().consume() {
+        match token {
+            Token::Semicolon => {
+                let _ = tq.increment();
+                break;
+            }
+            Token::TypeKwd | Token::TableKwd
+                if tq.get_idx() > start.get_idx() =>
+            {
+                break;
+            }
+            _ => {
+                let _ = tq.increment();
+            }
         }
     }
+    tq
+}
+
+// Recovers the `TokenError` from an error raised parsing the statement at
+// `start`. The parser only raises those, but anything else is put at the
+// start of the statement.
+fn token_error(start: &TokenQueue<Token>, error: anyhow::Error) -> TokenError {
+    error.downcast::<TokenError>().unwrap_or_else(|_| {
+        let found = start.clone().consume().ok();
+        TokenError::new(
+            start.get_idx(),
+            unexpected_token("a statement", found.as_ref()),
+        )
+    })
+}
+
+/// Parses every statement in `tq`. When a statement fails, its error is
+/// recorded and parsing carries on from the next statement, so the schema
+/// holds whatever could be parsed.
+fn parse_statements(
+    tq: &TokenQueue<Token>,
+    symtable: &mut SymbolTable,
+) -> (SpreadsheetSchema, Vec<TokenError>) {
+    let mut tq: TokenQueue<Token> = tq.clone();
+    let mut tables = HashMap::new();
+    let mut table_names = Vec::new();
+    let mut types = HashMap::new();
+    let mut type_names = Vec::new();
+    let mut errors = Vec::new();
+    while !tq.is_consumed() {
+        let stmt_tq = tq.clone();
+        let stmt = match tq.parse_with_mut(Stmt::parse, symtable) {
+            Ok(stmt) => stmt,
+            Err(e) => {
+                let e = token_error(&stmt_tq, e);
+                tq = resync(&stmt_tq, &e);
+                errors.push(e);
+                continue;
+            }
+        };
+        match stmt {
+            Stmt::TableSchema(schema_name, schema) => {
+                if tables.insert(schema_name.clone(), schema).is_some() {
+                    errors.push(TokenError::new(
+                        stmt_tq.get_idx(),
+                        SchemaError::DuplicateSymbol {
+                            name: schema_name.to_string(),
+                        },
+                    ));
+                } else {
+                    table_names.push(schema_name);
+                }
+            }
+            Stmt::TypeDef(type_name, data_type) => {
+                types.insert(type_name.clone(), data_type);
+                type_names.push(type_name);
+            }
+        }
+        if let Err(e) = expect(&mut tq, Token::Semicolon, "';'") {
+            let e = token_error(&stmt_tq, e);
+            tq = resync(&stmt_tq, &e);
+            errors.push(e);
+        }
+    }
+    (
+        SpreadsheetSchema::new(tables, table_names, types, type_names),
+        errors,
+    )
 }
 
 impl Parse for SpreadsheetSchema {
@@ -372,36 +1175,35 @@ impl Parse for SpreadsheetSchema {
         tq: &TokenQueue<Token>,
         symtable: &mut SymbolTable,
     ) -> ParseResult<Self> {
-        let mut tq: TokenQueue<Token> = tq.clone();
-        let mut tables = HashMap::new();
-        let mut table_names = Vec::new();
-        while let Ok(stmt) = tq.parse_with_mut(Stmt::parse, symtable) {
-            match stmt {
-                Stmt::TableSchema(schema_name, schema) => {
-                    if let Some(_) = tables.insert(schema_name.clone(), schema)
-                    {
-                        return Err(anyhow::anyhow!(
-                            "Can't have two table schemas named '{schema_name}'."
-                        ));
-                    }
-                    table_names.push(schema_name);
-                }
-                Stmt::TypeDef(_, _) => {}
+        let (schema, errors) = parse_statements(tq, symtable);
+        match errors.into_iter().next() {
+            Some(e) => Err(anyhow::Error::new(e)),
+            None => {
+                // every token is consumed when there are no errors
+                let mut tq = tq.clone();
+                while tq.increment().is_ok() {}
+                Ok((schema, tq.get_idx()))
             }
-            tq.consume_eq(Token::Semicolon)?;
         }
-        Ok((SpreadsheetSchema::new(tables, table_names), tq.get_idx()))
     }
 }
 
+/// Parses a schema, carrying on past statements that fail. Returns what
+/// could be parsed along with every error found.
+pub fn parse_spreadsheet_schema_with_recovery(
+    tq: &TokenQueue<Token>,
+) -> (SpreadsheetSchema, Vec<TokenError>) {
+    parse_statements(tq, &mut HashMap::new())
+}
+
+/// Parses a schema, failing with the first error found and the token it
+/// occurred at.
 pub fn parse_spreadsheet_schema(
     tq: &TokenQueue<Token>,
-) -> anyhow::Result<SpreadsheetSchema> {
-    let mut tq = tq.clone();
-    let mut symtable = HashMap::new();
-    let schema = tq.parse_with_mut(SpreadsheetSchema::parse, &mut symtable)?;
-    if !tq.is_consumed() {
-        return Err(anyhow::anyhow!("Schema ended prematurely."));
+) -> Result<SpreadsheetSchema, TokenError> {
+    let (schema, errors) = parse_statements(tq, &mut HashMap::new());
+    match errors.into_iter().next() {
+        Some(e) => Err(e),
+        None => Ok(schema),
     }
-    Ok(schema)
 }
@@ -0,0 +1,260 @@
+use std::fmt::Display;
+
+use crate::{error::SchemaError, ql::lex::Token};
+
+/// A range of bytes in the source text of a schema.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Span {
+    start: usize,
+    end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    pub fn get_start(&self) -> usize {
+        self.start
+    }
+
+    pub fn get_end(&self) -> usize {
+        self.end
+    }
+
+    /// The line and column the span starts at in `source`, both counting
+    /// from 1. Columns are counted in characters.
+    pub fn get_line_col(&self, source: &str) -> (usize, usize) {
+        let before = &source[..self.start.min(source.len())];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+        (line, before[line_start..].chars().count() + 1)
+    }
+}
+
+/// An error raised while parsing, at the token the parser was up to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenError {
+    idx: usize,
+    error: SchemaError,
+}
+
+impl TokenError {
+    pub fn new(idx: usize, error: SchemaError) -> Self {
+        Self { idx, error }
+    }
+
+    /// The index of the token in the token queue.
+    pub fn get_idx(&self) -> usize {
+        self.idx
+    }
+
+    pub fn get_error(&self) -> &SchemaError {
+        &self.error
+    }
+
+    /// Converts to an `anyhow::Error` that downcasts to both this and the
+    /// `SchemaError`.
+    pub fn into_error(self) -> anyhow::Error {
+        anyhow::Error::new(self.error.clone()).context(self)
+    }
+}
+
+impl Display for TokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
+
+impl std::error::Error for TokenError {}
+
+/// The part of a schema that a validation error is about. Names are owned
+/// so that errors can be sent between threads.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Subject {
+    Type(String),
+    Table(String),
+    Column(String, String),
+}
+
+impl Subject {
+    /// Finds the index of the token that names the subject where it's
+    /// defined.
+    pub fn find(&self, tokens: &[Token]) -> Option<usize> {
+        let name_at = |idx: usize, name: &str| {
+            tokens
+                .get(idx)
+                .and_then(|tok| tok.get_ident_or_str_literal())
+                .is_some_and(|ident| &*ident == name)
+        };
+        let find_def = |kwd: Token, name: &str| {
+            (1..tokens.len())
+                .find(|idx| tokens[idx - 1] == kwd && name_at(*idx, name))
+        };
+
+        match self {
+            Self::Type(name) => find_def(Token::TypeKwd, name),
+            Self::Table(name) => find_def(Token::TableKwd, name),
+            Self::Column(table, column) => {
+                let start = find_def(Token::TableKwd, table)?;
+                (start + 1..tokens.len())
+                    .take_while(|idx| tokens[*idx] != Token::Semicolon)
+                    .find(|idx| {
+                        name_at(*idx, column)
+                            && tokens.get(idx + 1) == Some(&Token::Colon)
+                    })
+            }
+        }
+    }
+}
+
+/// An error raised while validating a schema, about one part of it. The
+/// message names the part before saying what's wrong with it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubjectError {
+    subject: Subject,
+    message: String,
+    error: Box<SchemaError>,
+}
+
+impl SubjectError {
+    pub fn new(subject: Subject, message: String, error: SchemaError) -> Self {
+        Self {
+            subject,
+            message,
+            error: Box::new(error),
+        }
+    }
+
+    pub fn get_subject(&self) -> &Subject {
+        &self.subject
+    }
+
+    pub fn get_error(&self) -> &SchemaError {
+        &self.error
+    }
+
+    /// Converts to an `anyhow::Error` that downcasts to both this and the
+    /// `SchemaError`.
+    pub fn into_error(self) -> anyhow::Error {
+        anyhow::Error::new(*self.error.clone()).context(self)
+    }
+}
+
+impl Display for SubjectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for SubjectError {}
+
+/// Finds the span of the source that `error` is about, if it was raised at a
+/// token or is about a part of the schema.
+pub fn find_span(
+    error: &anyhow::Error,
+    tokens: &[Token],
+    spans: &[Span],
+) -> Option<Span> {
+    let idx = if let Some(e) = error.downcast_ref::<TokenError>() {
+        e.get_idx()
+    } else if let Some(e) = error.downcast_ref::<SubjectError>() {
+        e.get_subject().find(tokens)?
+    } else {
+        return None;
+    };
+    // errors at the end of the input point just past the last token
+    Some(spans.get(idx).copied().unwrap_or_else(|| {
+        let end = spans.last().map_or(0, |span| span.end);
+        Span::new(end, end)
+    }))
+}
+
+/// An error located in the source text of a schema. It's displayed with the
+/// line and column it occurred at, followed by the offending line with a
+/// caret under the span.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceError {
+    span: Span,
+    line: usize,
+    column: usize,
+    message: String,
+    snippet: String,
+}
+
+impl SourceError {
+    pub fn new(span: Span, source: &str, message: String) -> Self {
+        let (line, column) = span.get_line_col(source);
+        let text = source.lines().nth(line - 1).unwrap_or("");
+        // the caret covers the span, up to the end of the line
+        let width = source
+            [span.start.min(source.len())..span.end.min(source.len())]
+            .lines()
+            .next()
+            .map_or(1, |text| text.chars().count().max(1));
+        let gutter = " ".repeat(line.to_string().len());
+        let snippet = format!(
+            "{gutter} |\n{line} | {text}\n{gutter} | {}{}",
+            " ".repeat(column - 1),
+            "^".repeat(width)
+        );
+        Self {
+            span,
+            line,
+            column,
+            message,
+            snippet,
+        }
+    }
+
+    /// Locates `error` in `source`, if it was raised at a token or is about
+    /// a part of the schema, attaching the location as context so the
+    /// original error can still be downcast to. `tokens` and `spans` are
+    /// what `lex_with_spans` returned for `source`.
+    pub fn locate(
+        error: anyhow::Error,
+        source: &str,
+        tokens: &[Token],
+        spans: &[Span],
+    ) -> anyhow::Error {
+        match find_span(&error, tokens, spans) {
+            Some(span) => {
+                let located = Self::new(span, source, error.to_string());
+                error.context(located)
+            }
+            None => error,
+        }
+    }
+
+    pub fn get_span(&self) -> Span {
+        self.span
+    }
+
+    pub fn get_line(&self) -> usize {
+        self.line
+    }
+
+    pub fn get_column(&self) -> usize {
+        self.column
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn get_snippet(&self) -> &str {
+        &self.snippet
+    }
+}
+
+impl Display for SourceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}\n{}",
+            self.line, self.column, self.message, self.snippet
+        )
+    }
+}
+
+impl std::error::Error for SourceError {}
@@ -0,0 +1,59 @@
+/// Translates a data type to Rust, for generating typed rows.
+pub trait ToRust {
+    /// The Rust type of a value of this type, as an `Option` if it's
+    /// nullable.
+    fn rust_type(&self) -> String;
+
+    /// An expression converting `field`, a value of `rust_type`, to an
+    /// `Option<Literal>`.
+    fn rust_literal(&self, field: &str) -> String;
+}
+
+const RUST_KEYWORDS: [&str; 52] = [
+    "abstract", "as", "async", "await", "become", "box", "break", "const",
+    "continue", "crate", "do", "dyn", "else", "enum", "extern", "false",
+    "final", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "macro",
+    "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "self", "Self", "static", "struct", "super", "trait", "true", "try",
+    "type", "typeof", "unsafe", "unsized", "use", "virtual", "where", "while",
+    "yield",
+];
+
+/// Formats a name as a Rust identifier. Characters that can't appear in one
+/// are replaced with underscores, and names that don't start with a letter
+/// or underscore get one prepended. Keywords become raw identifiers, except
+/// the few that can't be, which get an underscore appended.
+pub fn rust_ident(name: &str) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c,
+            false => '_',
+        })
+        .collect();
+    if !ident.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        ident.insert(0, '_');
+    }
+    match ident.as_str() {
+        "_" | "crate" | "self" | "Self" | "super" => ident + "_",
+        _ if RUST_KEYWORDS.contains(&ident.as_str()) => format!("r#{ident}"),
+        _ => ident,
+    }
+}
+
+/// Formats each of `names` as a distinct Rust identifier, numbering any that
+/// would otherwise be the same.
+pub fn rust_idents(names: &[impl AsRef<str>]) -> Vec<String> {
+    let mut idents: Vec<String> = Vec::new();
+    for name in names {
+        let base = rust_ident(name.as_ref());
+        let mut ident = base.clone();
+        let mut n = 1;
+        while idents.contains(&ident) {
+            n += 1;
+            ident = format!("{base}_{n}");
+        }
+        idents.push(ident);
+    }
+    idents
+}
@@ -0,0 +1,26 @@
+/// The SQL databases that schemas can be translated for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SqlDialect {
+    Sqlite,
+    Postgres,
+}
+
+/// Translates a data type to the parts of a SQL column definition.
+pub trait ToSql {
+    /// The SQL type of a column holding values of this type.
+    fn sql_type(&self, dialect: SqlDialect) -> String;
+
+    /// Conditions that a value in `column` must satisfy, for use in `CHECK`
+    /// constraints. `column` is already quoted.
+    fn sql_checks(&self, column: &str, dialect: SqlDialect) -> Vec<String>;
+}
+
+/// Quotes an identifier so it can't clash with SQL keywords.
+pub fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+/// Quotes a string as a SQL string literal.
+pub fn quote_str(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
@@ -1,16 +1,31 @@
 use crate::{
-    core::schema::SpreadsheetSchema,
+    core::{
+        data::{Spreadsheet, infer::infer_ddl},
+        schema::{
+            ColumnSchema, DataType, DecDataType, IntDataType, SharedDataType,
+            SpreadsheetSchema, StrDataType, TableSchema, check::Expr,
+            violation::ConstraintKind,
+        },
+    },
+    error::SchemaError,
+    json::{FromJson, ToJson},
     ql::{
         Stmt,
-        lex::{Token, setup_lexer},
-        parse::{Parse, parse_spreadsheet_schema},
+        diagnostic::Severity,
+        lex::{Literal, Token, lex_with_spans, setup_lexer},
+        parse::{
+            Parse, parse_spreadsheet_schema,
+            parse_spreadsheet_schema_with_recovery,
+        },
+        span::{SourceError, Span, Subject},
     },
+    sql::{SqlDialect, ToSql},
 };
 use rlrl::parse::TokenQueue;
 use std::{collections::HashMap, fs};
 
-const NUM_VALID_TEST_SCHEMA: usize = 4;
-const NUM_INVALID_TEST_SCHEMA: usize = 5;
+const NUM_VALID_TEST_SCHEMA: usize = 5;
+const NUM_INVALID_TEST_SCHEMA: usize = 11;
 
 fn lex_file(path: &str) -> anyhow::Result<TokenQueue<Token>> {
     let s = fs::read_to_string(path)?.replace("\r", "");
@@ -170,3 +185,1516 @@ fn test_schema_validation() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_bool_type() -> anyhow::Result<()> {
+    // bool columns, with and without defaults
+    assert_maps_to_schema("table T (done: bool, archived: bool? = false);")?;
+    assert_maps_to_stmt("type flag bool;")?;
+
+    // bool columns only accept boolean literals
+    let schema = parse_schema_from_str("table T (done: bool);")?;
+    let column_type = schema
+        .get_table("T")
+        .unwrap()
+        .get_column("done")
+        .unwrap()
+        .get_type();
+    assert!(
+        column_type
+            .validate_literal(Some(&Literal::Bool(true)))
+            .is_ok()
+    );
+    assert!(
+        column_type
+            .validate_literal(Some(&Literal::Int(1)))
+            .is_err()
+    );
+    assert!(column_type.validate_literal(None).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_temporal_types() -> anyhow::Result<()> {
+    // date, time and datetime columns round-trip with their bounds
+    assert_maps_to_schema(
+        "table T (created: datetime<, >, due: date<2020-01-01, >?, opens: time<09:00:00, 17:30:00> = 12:00:00);",
+    )?;
+
+    // bounds may also be written as ISO-8601 strings
+    assert!(
+        parse_schema_from_str("table T (due: date<\"2020-01-01\", >);")?
+            .to_string()
+            .trim()
+            == "table T (due: date<2020-01-01, >);"
+    );
+
+    // the bounds are enforced by the validator
+    let schema = parse_schema_from_str("table T (due: date<2020-01-01, >);")?;
+    let column_type = schema
+        .get_table("T")
+        .unwrap()
+        .get_column("due")
+        .unwrap()
+        .get_type();
+    let lit = |s: &str| Literal::Str(s.into());
+    assert!(
+        column_type
+            .validate_literal(Some(&lit("2021-06-30")))
+            .is_ok()
+    );
+    assert!(
+        column_type
+            .validate_literal(Some(&lit("2019-12-31")))
+            .is_err()
+    );
+    assert!(
+        column_type
+            .validate_literal(Some(&lit("yesterday")))
+            .is_err()
+    );
+
+    // strings are read in the formats values are written in, so nothing is
+    // lost on the way back out
+    assert_eq!(
+        lit("12:00:05").get_time(),
+        Some(chrono::NaiveTime::from_hms_opt(12, 0, 5).unwrap())
+    );
+    assert_eq!(lit("12:00:05.5").get_time(), None);
+    assert!(lit("2024-01-01T12:00:05").get_datetime().is_some());
+    assert_eq!(lit("2024-01-01T12:00:05.5").get_datetime(), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_enum_type() -> anyhow::Result<()> {
+    // inline enums and enum typedefs
+    assert_maps_to_schema(
+        "table Tickets (status: enum(\"draft\", \"open\", \"closed\") = \"draft\", priority: enum(\"low\", \"high\")?);",
+    )?;
+    assert_maps_to_stmt("type Status enum(\"draft\", \"open\", \"closed\");")?;
+
+    // only the declared variants are accepted
+    let schema = parse_schema_from_str(
+        "type Status enum(\"draft\", \"open\", \"closed\"); table T (s: Status);",
+    )?;
+    let column_type = schema
+        .get_table("T")
+        .unwrap()
+        .get_column("s")
+        .unwrap()
+        .get_type();
+    assert!(
+        column_type
+            .validate_literal(Some(&Literal::Str("open".into())))
+            .is_ok()
+    );
+    let err = column_type
+        .validate_literal(Some(&Literal::Str("done".into())))
+        .unwrap_err();
+    // strings are only quoted where a schema is written out
+    assert_eq!(
+        err.to_string(),
+        "Value 'done' is not one of the enum variants."
+    );
+    assert!(
+        column_type
+            .validate_literal(Some(&Literal::Int(0)))
+            .is_err()
+    );
+
+    // enums must have distinct variants
+    assert!(
+        crate::parse_valid_schema_from_str("table T (s: enum(\"a\", \"a\"));")
+            .is_err()
+    );
+    assert!(
+        crate::parse_valid_schema_from_str("table T (s: enum());").is_err()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_str_pattern() -> anyhow::Result<()> {
+    // patterns round-trip, before the nullable marker
+    assert_maps_to_schema(
+        "table Users (userName: str<2, 32> ~ \"^[a-z0-9_]+$\", postcode: str<, > ~ \"^[0-9]{4}$\"?);",
+    )?;
+
+    // the pattern is enforced by the validator
+    let schema =
+        parse_schema_from_str("table T (sku: str ~ \"^[A-Z]{3}-[0-9]+$\");")?;
+    let column_type = schema
+        .get_table("T")
+        .unwrap()
+        .get_column("sku")
+        .unwrap()
+        .get_type();
+    assert!(
+        column_type
+            .validate_literal(Some(&Literal::Str("ABC-123".into())))
+            .is_ok()
+    );
+    assert!(
+        column_type
+            .validate_literal(Some(&Literal::Str("abc-123".into())))
+            .is_err()
+    );
+
+    // invalid patterns are rejected when the schema is parsed
+    assert!(parse_schema_from_str("table T (a: str ~ \"(\");").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_long_and_dec_types() -> anyhow::Result<()> {
+    assert_maps_to_schema(
+        "table Orders (orderId: long<0, >, total: dec<10, 2> = 19.99, tax: dec<4, 2>?);",
+    )?;
+
+    // integers too large for an i32 are lexed as longs
+    let schema = parse_schema_from_str(
+        "table T (id: long<, 9000000000>, price: dec<6, 2>);",
+    )?;
+    let table = schema.get_table("T").unwrap();
+    let id_type = table.get_column("id").unwrap().get_type();
+    assert!(
+        id_type
+            .validate_literal(Some(&Literal::Long(8_000_000_000)))
+            .is_ok()
+    );
+    assert!(
+        id_type
+            .validate_literal(Some(&Literal::Long(9_000_000_001)))
+            .is_err()
+    );
+    assert!(id_type.validate_literal(Some(&Literal::Int(7))).is_ok());
+
+    // decimals are exact, and bounded by precision and scale
+    let price_type = table.get_column("price").unwrap().get_type();
+    let dec = |s: &str| Literal::Dec(s.parse().unwrap());
+    assert!(price_type.validate_literal(Some(&dec("9999.99"))).is_ok());
+    assert!(price_type.validate_literal(Some(&dec("9999.990"))).is_ok());
+    assert!(price_type.validate_literal(Some(&dec("10000.00"))).is_err());
+    assert!(price_type.validate_literal(Some(&dec("0.001"))).is_err());
+
+    // a type that hasn't been validated doesn't panic on values
+    let bad_type = DecDataType::new(2, 3, false);
+    assert!(bad_type.validate_literal(Some(&dec("1.5"))).is_err());
+    assert!(bad_type.validate_literal(Some(&dec("0.123"))).is_ok());
+    assert_eq!(
+        bad_type.sql_checks("a", SqlDialect::Sqlite),
+        vec!["abs(a) < 1"]
+    );
+    assert!(
+        price_type
+            .validate_literal(Some(&Literal::Dbl(0.1)))
+            .is_ok()
+    );
+
+    // scale can't exceed precision
+    assert!(
+        crate::parse_valid_schema_from_str("table T (a: dec<2, 3>);").is_err()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_primary_key() -> anyhow::Result<()> {
+    // single and composite keys round-trip
+    assert_maps_to_schema(
+        "table Users (userId: int<0, >, userName: str<2, 32>, primary key (userId));",
+    )?;
+    assert_maps_to_schema(
+        "table MovieRatings (movieId: int<0, >, userId: int<0, >, rating: dbl<0, 5>, primary key (movieId, userId));",
+    )?;
+
+    // the `primary` keyword is optional, and the key can be declared anywhere
+    let schema = crate::parse_valid_schema_from_str(
+        "table T (key (a), a: int, b: str);",
+    )?;
+    assert_eq!(
+        schema.get_table("T").unwrap().get_primary_key(),
+        Some(&["a".into()] as &[std::rc::Rc<str>])
+    );
+
+    // constraint and operator keywords can still name columns and types
+    let schema = crate::parse_valid_schema_from_str(
+        "type key int<0, >;\ntable T (primary: key, key: str, check: bool, primary key (primary, key), check check or key is not null);",
+    )?;
+    let table = schema.get_table("T").unwrap();
+    assert_eq!(
+        table.get_column_names(),
+        &["primary".into(), "key".into(), "check".into()]
+            as &[std::rc::Rc<str>]
+    );
+    assert_eq!(table.get_primary_key().unwrap().len(), 2);
+    assert_eq!(
+        table.get_checks()[0].to_string(),
+        "check or key is not null"
+    );
+    crate::parse_valid_schema_from_str(&format!("{schema:#}"))?;
+
+    // key columns must exist and must not be nullable
+    for input in [
+        "table T (a: int, key (b));",
+        "table T (a: int?, key (a));",
+        "table T (a: int, key (a, a));",
+        "table T (a: int, b: int, key (a), key (b));",
+    ] {
+        assert!(
+            crate::parse_valid_schema_from_str(input).is_err(),
+            "{input}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_foreign_keys() -> anyhow::Result<()> {
+    let schema = parse_valid_schema_from_file(
+        "test_artifacts/valid_schemas/input/input_5.txt",
+    )?;
+    let json = schema.to_json();
+    assert_eq!(
+        json["tables"]["MovieRatings"]["columns"]["movieId"]["references"],
+        serde_json::json!({"table": "Movies", "column": "movieId"})
+    );
+    assert!(
+        json["tables"]["MovieRatings"]["columns"]["rating"]
+            .get("references")
+            .is_none()
+    );
+
+    // int and long columns can reference each other
+    crate::parse_valid_schema_from_str(
+        "table A (id: long); table B (aId: int references A(id));",
+    )?;
+
+    Ok(())
+}
+
+#[test]
+fn test_unique_constraints() -> anyhow::Result<()> {
+    assert_maps_to_schema(
+        "table Users (userId: int<0, >, email: str<, >, org: int<, >, primary key (userId), unique (email), unique (org, userId));",
+    )?;
+
+    // column-level `unique` is shorthand for a single-column constraint
+    let schema = crate::parse_valid_schema_from_str(
+        "table T (id: int, email: str unique, a: int, b: int, key (id), unique (a, b));",
+    )?;
+    let table = schema.get_table("T").unwrap();
+    assert_eq!(table.get_unique_constraints().len(), 2);
+
+    // duplicates are reported against the first occurrence
+    let row = |id: i32, email: Option<&str>, a: i32, b: i32| {
+        vec![
+            Some(Literal::Int(id)),
+            email.map(|email| Literal::Str(email.into())),
+            Some(Literal::Int(a)),
+            Some(Literal::Int(b)),
+        ]
+    };
+    let rows = vec![
+        row(1, Some("a@x.com"), 1, 1),
+        row(2, Some("b@x.com"), 1, 2),
+        row(1, Some("a@x.com"), 2, 1),
+        row(3, None, 1, 2),
+        row(4, None, 3, 3),
+    ];
+    let violations = table.find_unique_violations(&rows);
+    let found: Vec<(&str, usize, usize)> = violations
+        .iter()
+        .map(|v| {
+            (
+                &*v.get_columns()[0],
+                v.get_first_row(),
+                v.get_duplicate_row(),
+            )
+        })
+        .collect();
+    assert_eq!(found, vec![("id", 0, 2), ("email", 0, 2), ("a", 1, 3)]);
+
+    // dates match strings holding the same date
+    let schema = crate::parse_valid_schema_from_str(
+        "table T (d: date, t: time, key (d, t));",
+    )?;
+    let table = schema.get_table("T").unwrap();
+    let rows = vec![
+        vec![
+            Some(Literal::Date("2024-01-01".parse()?)),
+            Some(Literal::Time("12:00:00".parse()?)),
+        ],
+        vec![
+            Some(Literal::Str("2024-01-01".into())),
+            Some(Literal::Str("12:00:00".into())),
+        ],
+    ];
+    assert_eq!(table.find_unique_violations(&rows).len(), 1);
+
+    // constraints on unknown columns, which validation rejects, are skipped
+    // rather than treating every row as a duplicate
+    let a_type: SharedDataType =
+        std::rc::Rc::new(IntDataType::new(None, None, false));
+    let table = TableSchema::new(
+        HashMap::from([(
+            "a".into(),
+            ColumnSchema::new(a_type, None, None, None),
+        )]),
+        vec!["a".into()],
+        None,
+        vec![vec!["b".into()]],
+        vec![],
+    );
+    let rows = vec![vec![Some(Literal::Int(1))], vec![Some(Literal::Int(2))]];
+    assert!(table.find_unique_violations(&rows).is_empty());
+
+    // unique columns must exist
+    assert!(
+        crate::parse_valid_schema_from_str("table T (a: int, unique (b));")
+            .is_err()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_check_constraints() -> anyhow::Result<()> {
+    assert_maps_to_schema(
+        "table T (start: date<, >, end: date<, >, price: dbl<, >, discount: dbl<, >, check end >= start, check discount <= price and discount >= 0);",
+    )?;
+    assert_maps_to_schema(
+        "table T (a: int<, >, b: int<, >?, c: int<, >, check (a + b) * 2 > c or b is not null);",
+    )?;
+
+    // `a-1` lexes as `a`, `-1` but still parses as a subtraction
+    let schema = crate::parse_valid_schema_from_str(
+        "table T (a: int, b: int?, check a-1 > 0, check not (b is null) or a < 10);",
+    )?;
+    let table = schema.get_table("T").unwrap();
+    assert_eq!(table.get_checks()[0].to_string(), "a - 1 > 0");
+
+    // negating a negative number never writes `--`, which starts a SQL comment
+    let negated = crate::parse_valid_schema_from_str(
+        "table T (a: int, check a > --1 and a < - -2);",
+    )?;
+    let negated_table = negated.get_table("T").unwrap();
+    let check = &negated_table.get_checks()[0];
+    assert_eq!(check.to_string(), "a > -(-1) and a < -(-2)");
+    assert_eq!(
+        check.to_sql(SqlDialect::Sqlite),
+        "(\"a\" > (-(-1))) AND (\"a\" < (-(-2)))"
+    );
+    assert_maps_to_schema(&negated.to_string())?;
+
+    let row = |a: i32, b: Option<i32>| {
+        vec![Some(Literal::Int(a)), b.map(Literal::Int)]
+    };
+    assert!(table.find_check_violations(&row(5, Some(1)))?.is_empty());
+    assert_eq!(table.find_check_violations(&row(1, Some(1)))?.len(), 1);
+    assert_eq!(table.find_check_violations(&row(20, None))?.len(), 1);
+    assert_eq!(table.find_check_violations(&row(0, None))?.len(), 1);
+
+    // a check that evaluates to null passes
+    let schema =
+        crate::parse_valid_schema_from_str("table T (a: int?, check a > 0);")?;
+    let table = schema.get_table("T").unwrap();
+    assert!(table.find_check_violations(&vec![None])?.is_empty());
+
+    // checks must reference known columns and type-check as booleans
+    for schema in [
+        "table T (a: int, check b > 0);",
+        "table T (a: int, b: str, check a = b);",
+        "table T (a: int, check a + 1);",
+        "table T (a: bool, check -a);",
+        "table T (a: bool, check a and);",
+        "table T (a: bool, check a is not);",
+    ] {
+        assert!(crate::parse_valid_schema_from_str(schema).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_default_validation() -> anyhow::Result<()> {
+    crate::parse_valid_schema_from_str(
+        "table T (a: int<0, 10> = 5, b: str<, 3> = \"abc\", c: dbl? = 1);",
+    )?;
+
+    // errors name the offending table and column
+    let err = crate::parse_valid_schema_from_str(
+        "table T (a: int, b: int<0, 10> = 99);",
+    )
+    .err()
+    .unwrap();
+    let err = err.downcast_ref::<SourceError>().unwrap();
+    assert!(err.get_message().starts_with("Column 'T.b'"));
+
+    for schema in [
+        "table T (a: int = \"hello\");",
+        "table T (a: str<, 3> = \"toolong\");",
+        "table T (a: str<4, > = \"abc\");",
+        "table T (a: bool = 1);",
+        "table T (a: enum(\"x\", \"y\") = \"z\");",
+    ] {
+        assert!(crate::parse_valid_schema_from_str(schema).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_table_data() -> anyhow::Result<()> {
+    let schema = crate::parse_valid_schema_from_str(
+        "table Items (id: int<0, >, name: str<1, >, qty: int<0, > = 1, note: str? = \"none\", check qty < 100);",
+    )?;
+    let mut spreadsheet = Spreadsheet::new(&schema);
+    assert_eq!(spreadsheet.get_num_tables(), 1);
+
+    // missing columns take their default, or null
+    let idx = spreadsheet.insert(
+        "Items",
+        HashMap::from([
+            ("id", Some(Literal::Int(1))),
+            ("name", Some(Literal::Str("pen".into()))),
+        ]),
+    )?;
+    let table = spreadsheet.get_table("Items").unwrap();
+    assert_eq!(
+        table.get_row(idx).unwrap(),
+        &vec![
+            Some(Literal::Int(1)),
+            Some(Literal::Str("pen".into())),
+            Some(Literal::Int(1)),
+            Some(Literal::Str("none".into())),
+        ]
+    );
+    assert_eq!(table.get_value(idx, "qty"), Some(&Literal::Int(1)));
+
+    // columns given as null stay null, even with a default
+    let idx = spreadsheet.insert(
+        "Items",
+        HashMap::from([
+            ("id", Some(Literal::Int(2))),
+            ("name", Some(Literal::Str("pad".into()))),
+            ("note", None),
+        ]),
+    )?;
+    let table = spreadsheet.get_table("Items").unwrap();
+    assert_eq!(table.get_value(idx, "note"), None);
+    assert_eq!(table.get_value(idx, "qty"), Some(&Literal::Int(1)));
+
+    // rows given in column order are validated the same way
+    let table = spreadsheet.get_table_mut("Items").unwrap();
+    table.insert_row(vec![
+        Some(Literal::Int(3)),
+        Some(Literal::Str("ink".into())),
+        Some(Literal::Int(5)),
+        Some(Literal::Str("blue".into())),
+    ])?;
+    assert_eq!(table.get_num_rows(), 3);
+
+    // invalid rows are rejected and not stored
+    for row in [
+        vec![
+            Some(Literal::Int(-1)),
+            Some(Literal::Str("a".into())),
+            None,
+            None,
+        ],
+        vec![Some(Literal::Int(3)), None, None, None],
+        vec![
+            Some(Literal::Int(3)),
+            Some(Literal::Str("a".into())),
+            Some(Literal::Int(200)),
+            None,
+        ],
+        vec![Some(Literal::Int(3))],
+    ] {
+        assert!(table.insert_row(row).is_err());
+    }
+    assert!(
+        table
+            .insert(HashMap::from([(
+                "colour",
+                Some(Literal::Str("red".into()))
+            )]))
+            .is_err()
+    );
+    assert!(table.insert(HashMap::from([("id", None)])).is_err());
+    assert_eq!(table.get_num_rows(), 3);
+
+    assert!(spreadsheet.insert("Unknown", HashMap::new()).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_validate_row() -> anyhow::Result<()> {
+    let schema = crate::parse_valid_schema_from_str(
+        "table T (a: int<0, 10>, b: str<, 3> ~ \"^[a-z]*$\", c: dec<4, 2>?, d: enum(\"x\", \"y\"), e: bool);",
+    )?;
+    let table = schema.get_table("T").unwrap();
+
+    let ok = vec![
+        Some(Literal::Int(5)),
+        Some(Literal::Str("ab".into())),
+        None,
+        Some(Literal::Str("x".into())),
+        Some(Literal::Bool(true)),
+    ];
+    assert!(table.validate_row(&ok).is_empty());
+
+    // every bad cell is reported, in column order
+    let bad = vec![
+        Some(Literal::Int(11)),
+        Some(Literal::Str("AB".into())),
+        Some(Literal::Dec("1.234".parse()?)),
+        Some(Literal::Str("z".into())),
+        Some(Literal::Int(1)),
+    ];
+    let violations = table.validate_row(&bad);
+    let found: Vec<(&str, ConstraintKind, Option<&Literal>)> = violations
+        .iter()
+        .map(|v| (v.get_column(), v.get_kind(), v.get_value()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("a", ConstraintKind::Max, bad[0].as_ref()),
+            ("b", ConstraintKind::Pattern, bad[1].as_ref()),
+            ("c", ConstraintKind::Scale, bad[2].as_ref()),
+            ("d", ConstraintKind::Variant, bad[3].as_ref()),
+            ("e", ConstraintKind::TypeMismatch, bad[4].as_ref()),
+        ]
+    );
+
+    // missing values count as nulls
+    let kinds: Vec<ConstraintKind> = table
+        .validate_row(&[Some(Literal::Int(-1))])
+        .iter()
+        .map(|v| v.get_kind())
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ConstraintKind::Min,
+            ConstraintKind::Null,
+            ConstraintKind::Null,
+            ConstraintKind::Null,
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_csv_import() -> anyhow::Result<()> {
+    let schema = crate::parse_valid_schema_from_str(
+        "table Films (id: int<0, >, title: str<1, >, rating: dec<3, 1>?, released: date, seen: bool = false, key (id));",
+    )?;
+    let mut spreadsheet = Spreadsheet::new(&schema);
+
+    // headers can be in any order, and missing columns take their default
+    let csv = "title,id,released,rating
+Alien,1,1979-05-25,8.5
+\"Heat, the film\",2,1995-12-15,
+Up,x,2009-05-29,8.3
+,4,2010-01-01,9.99
+Jaws,5,1975-06-20
+Tron,6,1982-07-09,7.1
+";
+    let report = spreadsheet.import_csv("Films", csv.as_bytes())?;
+    assert_eq!(report.get_num_imported(), 3);
+    assert_eq!(report.get_num_rejected(), 3);
+
+    let found: Vec<(u64, Option<usize>)> = report
+        .get_errors()
+        .iter()
+        .map(|e| (e.get_line(), e.get_column()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![(4, Some(2)), (5, Some(1)), (5, Some(4)), (6, None)]
+    );
+
+    let table = spreadsheet.get_table("Films").unwrap();
+    assert_eq!(
+        table.get_row(1).unwrap(),
+        &vec![
+            Some(Literal::Int(2)),
+            Some(Literal::Str("Heat, the film".into())),
+            None,
+            Some(Literal::Date("1995-12-15".parse()?)),
+            Some(Literal::Bool(false)),
+        ]
+    );
+    assert_eq!(
+        table.get_value(2, "title"),
+        Some(&Literal::Str("Tron".into()))
+    );
+
+    // unknown or repeated headers fail the whole import
+    let table = spreadsheet.get_table_mut("Films").unwrap();
+    assert!(table.import_csv("id,colour\n1,red\n".as_bytes()).is_err());
+    assert!(table.import_csv("id,id\n1,1\n".as_bytes()).is_err());
+    assert_eq!(table.get_num_rows(), 3);
+
+    // rows that can't be read, or that duplicate a key already in the table
+    // or earlier in the file, only reject themselves
+    let csv = b"id,title,released
+1,Alien,1979-05-25
+7,Brazil,1985-02-20
+7,Brazil,1985-02-20
+8,\xff,2000-01-01
+9,Dune,1984-12-14
+";
+    let report = table.import_csv(&csv[..])?;
+    assert_eq!(report.get_num_imported(), 2);
+    let found: Vec<(u64, Option<usize>)> = report
+        .get_errors()
+        .iter()
+        .map(|e| (e.get_line(), e.get_column()))
+        .collect();
+    assert_eq!(found, vec![(2, None), (4, None), (5, None)]);
+    assert_eq!(
+        report.get_errors()[0].get_message(),
+        "Values for (id) are already in the table"
+    );
+    assert_eq!(table.get_num_rows(), 5);
+
+    // non-finite doubles and checks that can't be evaluated only reject
+    // their own row
+    let schema = crate::parse_valid_schema_from_str(
+        "table T (x: dbl?, a: dec<4, 2>, b: dec<4, 2>, check a / b > 0);",
+    )?;
+    let mut spreadsheet = Spreadsheet::new(&schema);
+    let table = spreadsheet.get_table_mut("T").unwrap();
+    let csv = "x,a,b\nNaN,1,1\ninf,1,1\n1,1,0\n2,1,2\n";
+    let report = table.import_csv(csv.as_bytes())?;
+    assert_eq!(report.get_num_imported(), 1);
+    let found: Vec<(u64, Option<usize>)> = report
+        .get_errors()
+        .iter()
+        .map(|e| (e.get_line(), e.get_column()))
+        .collect();
+    assert_eq!(found, vec![(2, Some(1)), (3, Some(1)), (4, None)]);
+
+    Ok(())
+}
+
+#[test]
+fn test_csv_export() -> anyhow::Result<()> {
+    let schema = crate::parse_valid_schema_from_str(
+        "table T (id: int, name: str?, score: dbl?, price: dec<6, 2>, at: date?);",
+    )?;
+    let mut spreadsheet = Spreadsheet::new(&schema);
+    let table = spreadsheet.get_table_mut("T").unwrap();
+    table.insert_row(vec![
+        Some(Literal::Int(1)),
+        Some(Literal::Str("Smith, \"Jo\"".into())),
+        Some(Literal::Dbl(0.1 + 0.2)),
+        Some(Literal::Dec("12.50".parse()?)),
+        Some(Literal::Str("2024-02-29".into())),
+    ])?;
+    table.insert_row(vec![
+        Some(Literal::Int(2)),
+        None,
+        Some(Literal::Int(3)),
+        Some(Literal::Int(7)),
+        None,
+    ])?;
+
+    let mut out = Vec::new();
+    spreadsheet.export_csv("T", &mut out, true)?;
+    let expected = "id,name,score,price,at
+1,\"Smith, \"\"Jo\"\"\",0.30000000000000004,12.50,2024-02-29
+2,,3,7,
+";
+    assert_eq!(String::from_utf8(out)?, expected);
+
+    // without a header, and read back in unchanged
+    let mut out = Vec::new();
+    let table = spreadsheet.get_table("T").unwrap();
+    table.export_csv(&mut out, false)?;
+    assert_eq!(out.iter().filter(|b| **b == b'\n').count(), 2);
+
+    let mut copy = Spreadsheet::new(&schema);
+    let report = copy.import_csv("T", expected.as_bytes())?;
+    assert_eq!(report.get_num_imported(), 2);
+    let mut round_trip = Vec::new();
+    copy.export_csv("T", &mut round_trip, true)?;
+    assert_eq!(String::from_utf8(round_trip)?, expected);
+
+    // header names are quoted wherever they need to be, and empty strings
+    // are told apart from nulls, both ways
+    let schema = crate::parse_valid_schema_from_str(
+        "table T (\"a\rb\": str?, \"c, d\": str?, e: int?);",
+    )?;
+    let mut spreadsheet = Spreadsheet::new(&schema);
+    let table = spreadsheet.get_table_mut("T").unwrap();
+    table.insert_row(vec![Some(Literal::Str("".into())), None, None])?;
+    let mut out = Vec::new();
+    spreadsheet.export_csv("T", &mut out, true)?;
+    let out = String::from_utf8(out)?;
+    assert_eq!(out, "\"a\rb\",\"c, d\",e\n\"\",,\n");
+
+    let mut copy = Spreadsheet::new(&schema);
+    let csv = format!("{out}\"\",\"x\"\"\",\"\"\n");
+    let report = copy.import_csv("T", csv.as_bytes())?;
+    assert_eq!(report.get_num_imported(), 2);
+    let table = copy.get_table("T").unwrap();
+    assert_eq!(
+        table.get_rows(),
+        &[
+            vec![Some(Literal::Str("".into())), None, None],
+            vec![
+                Some(Literal::Str("".into())),
+                Some(Literal::Str("x\"".into())),
+                None
+            ],
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_schema_inference() -> anyhow::Result<()> {
+    let csv = "Id,Unit Price,name,big,note,table
+3,2.5,pen,3000000000,,1
+1,10,ink,-4,n/a,x
+7,0.25,paper clip,12,,
+";
+    let ddl = infer_ddl("stock items", csv.as_bytes())?;
+    assert_eq!(
+        ddl,
+        "table \"stock items\" (Id: int<1, 7>, \"Unit Price\": dbl<0.25, 10>, name: str<3, 10>, big: long<-4, 3000000000>, note: str<3, 3>?, \"table\": str<1, 1>?);\n"
+    );
+
+    // the proposed schema is valid and accepts the data it came from
+    let schema = crate::parse_valid_schema_from_str(&ddl)?;
+    let mut spreadsheet = Spreadsheet::new(&schema);
+    let table = spreadsheet.get_table_mut("stock items").unwrap();
+    let report = table.import_csv(csv.as_bytes())?;
+    assert_eq!(report.get_num_imported(), 3);
+
+    // headers must be distinct, and writable as names
+    assert!(infer_ddl("T", "a, a\n1,2\n".as_bytes()).is_err());
+    assert!(infer_ddl("T", "\"a\"\"b\"\n1\n".as_bytes()).is_err());
+
+    // numbers whose bounds can't be written as doubles are decimals, or
+    // strings if they're too long for that, and the DDL still parses
+    let csv = "id,big,tiny\n12345678901234567890,1e300,1e-300\n2,3,4\n";
+    let ddl = infer_ddl("T", csv.as_bytes())?;
+    assert_eq!(
+        ddl,
+        "table T (id: dec<20, 0>, big: str<1, 5>, tiny: str<1, 6>);\n"
+    );
+    let schema = crate::parse_valid_schema_from_str(&ddl)?;
+    let mut spreadsheet = Spreadsheet::new(&schema);
+    let report = spreadsheet.import_csv("T", csv.as_bytes())?;
+    assert_eq!(report.get_num_imported(), 2);
+
+    // columns without any values are nullable strings
+    assert_eq!(
+        infer_ddl("T", "a,b\n1,\n".as_bytes())?,
+        "table T (a: int<1, 1>, b: str<, >?);\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_sqlite_ddl() -> anyhow::Result<()> {
+    let schema = crate::parse_valid_schema_from_str(
+        "table Users (id: int<0, >, name: str<1, 32>, score: dbl<0, 1>? = 0.5, active: bool = true, role: enum(\"admin\", \"user\") = \"user\", primary key (id));
+        table Orders (id: long, userId: int references Users(id), total: dec<8, 2>, placed: date<2020-01-01, >, unique (userId, placed), check total >= 0 or placed is null);",
+    )?;
+    let expected = r#"CREATE TABLE "Users" (
+    "id" INTEGER NOT NULL CHECK ("id" >= 0),
+    "name" TEXT NOT NULL CHECK (length("name") >= 1 AND length("name") <= 32),
+    "score" REAL DEFAULT 0.5 CHECK ("score" >= 0 AND "score" <= 1),
+    "active" INTEGER NOT NULL DEFAULT 1 CHECK ("active" IN (0, 1)),
+    "role" TEXT NOT NULL DEFAULT 'user' CHECK ("role" IN ('admin', 'user')),
+    PRIMARY KEY ("id")
+);
+
+CREATE TABLE "Orders" (
+    "id" INTEGER NOT NULL,
+    "userId" INTEGER NOT NULL REFERENCES "Users" ("id"),
+    "total" NUMERIC NOT NULL CHECK (abs("total") < 1000000),
+    "placed" TEXT NOT NULL CHECK ("placed" >= '2020-01-01'),
+    UNIQUE ("userId", "placed"),
+    CHECK (("total" >= 0) OR ("placed" IS NULL))
+);
+"#;
+    assert_eq!(schema.to_sql(SqlDialect::Sqlite), expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_postgres_ddl() -> anyhow::Result<()> {
+    let schema = crate::parse_valid_schema_from_str(
+        "type Email str<3, 254> ~ \"^[^@]+@[^@]+$\";
+        type Flag bool;
+        table Users (id: long<1, >, email: Email, backup: str<3, 254>?, admin: Flag = false, joined: datetime, balance: dec<10, 2>, primary key (id));",
+    )?;
+    assert_eq!(&*schema.get_type_names()[0], "Email");
+    let table = schema.get_table("Users").unwrap();
+    assert_eq!(
+        table.get_column("email").unwrap().get_type_alias(),
+        Some("Email")
+    );
+    assert_eq!(table.get_column("id").unwrap().get_type_alias(), None);
+
+    let expected = r#"CREATE DOMAIN "Email" AS TEXT CHECK (length(VALUE) >= 3 AND length(VALUE) <= 254 AND VALUE ~ '^[^@]+@[^@]+$');
+CREATE DOMAIN "Flag" AS BOOLEAN;
+
+CREATE TABLE "Users" (
+    "id" BIGINT NOT NULL CHECK ("id" >= 1),
+    "email" "Email" NOT NULL,
+    "backup" TEXT CHECK (length("backup") >= 3 AND length("backup") <= 254),
+    "admin" "Flag" NOT NULL DEFAULT FALSE,
+    "joined" TIMESTAMP NOT NULL,
+    "balance" NUMERIC(10, 2) NOT NULL,
+    PRIMARY KEY ("id")
+);
+"#;
+    assert_eq!(schema.to_sql(SqlDialect::Postgres), expected);
+
+    // SQLite has no domains, so named types are written out in full
+    assert!(
+        schema
+            .to_sql(SqlDialect::Sqlite)
+            .contains(r#""email" TEXT NOT NULL CHECK (length("email") >= 3"#)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_type_aliases() -> anyhow::Result<()> {
+    let tq = lex_file("test_artifacts/valid_schemas/input/input_2.txt")?;
+    let schema = parse_spreadsheet_schema(&tq)?;
+    assert_eq!(schema.get_type_names().len(), 2);
+    assert_eq!(schema.get_type("uIntType").unwrap().to_string(), "int<0, >");
+
+    // the alternate form keeps the `type` statements and aliases
+    let expected = "type uIntType int<0, >;
+type userNameType str<2, 32>;
+table Users (userId: uIntType, userName: userNameType, bio: str<, >?);
+";
+    assert_eq!(format!("{schema:#}"), expected);
+    assert_eq!(format!("{:#}", parse_schema_from_str(expected)?), expected);
+
+    // defaults and references follow the alias
+    let s = "type id int<1, >;
+table A (id: id);
+table B (aId: id = 1 references A(id));
+";
+    assert_eq!(format!("{:#}", crate::parse_valid_schema_from_str(s)?), s);
+
+    Ok(())
+}
+
+#[test]
+fn test_json_round_trip() -> anyhow::Result<()> {
+    for i in 1..(NUM_VALID_TEST_SCHEMA + 1) {
+        let path = format!("test_artifacts/valid_schemas/input/input_{i}.txt");
+        let schema = parse_valid_schema_from_file(&path)?;
+        let json = schema.to_json();
+        let loaded = crate::parse_valid_schema_from_json(&json)?;
+        assert_eq!(loaded.to_json(), json);
+        assert_eq!(loaded.to_string(), schema.to_string());
+    }
+
+    let schema = crate::parse_valid_schema_from_str(
+        "table T (a: int<0, 10> = 5, b: dbl<-1.5, >?, c: str<1, 8> ~ \"^[a-z]+$\" = \"abc\", d: dec<6, 2> = 1.25, e: date<2020-01-01, > = 2021-06-01, f: time?, g: enum(\"x\", \"y\"), h: long, primary key (a), unique (b, c), check a < 10 or b is null);",
+    )?;
+    let json = crate::compile_schema(&schema.to_string());
+    let loaded = crate::parse_valid_schema_from_json(&json)?;
+    assert_eq!(loaded.to_string(), schema.to_string());
+    assert_eq!(loaded.to_json(), json);
+
+    // named types and the columns that use them survive the round trip
+    let schema = crate::parse_valid_schema_from_str(
+        "type id int<1, >;\ntype unused str;\ntable T (a: id, b: id, c: int<1, >);",
+    )?;
+    let json = schema.to_json();
+    assert_eq!(json["tables"]["T"]["columns"]["a"]["type_alias"], "id");
+    let loaded = crate::parse_valid_schema_from_json(&json)?;
+    assert_eq!(loaded.to_json(), json);
+    assert_eq!(format!("{loaded:#}"), format!("{schema:#}"));
+    assert_eq!(loaded.get_type_names(), schema.get_type_names());
+    assert_eq!(loaded.find_unused_types(), vec!["unused".into()]);
+    let table = loaded.get_table("T").unwrap();
+    assert_eq!(table.get_column("a").unwrap().get_type_alias(), Some("id"));
+    assert_eq!(table.get_column("c").unwrap().get_type_alias(), None);
+
+    // decimals keep every digit
+    let schema = crate::parse_valid_schema_from_str(
+        "table T (a: dec<28, 2> = 1234567890123456789012345.67);",
+    )?;
+    let json = schema.to_json();
+    assert_eq!(
+        json["tables"]["T"]["columns"]["a"]["default_value"],
+        "1234567890123456789012345.67"
+    );
+    let loaded = crate::parse_valid_schema_from_json(&json)?;
+    assert_eq!(loaded.to_string(), schema.to_string());
+
+    // fractional defaults are stored as the column's type, so a dbl's default
+    // is written as a number
+    let schema = crate::parse_valid_schema_from_str(
+        "table T (a: dbl<0, 1>? = 0.5, b: long = 3, c: dbl = 2);",
+    )?;
+    let table = schema.get_table("T").unwrap();
+    assert_eq!(
+        table.get_column("a").unwrap().get_default_value(),
+        Some(&Literal::Dbl(0.5))
+    );
+    let json = schema.to_json();
+    assert_eq!(json["tables"]["T"]["columns"]["a"]["default_value"], 0.5);
+    let json_schemas = schema.to_json_schemas();
+    assert_eq!(json_schemas["T"]["properties"]["a"]["default"], 0.5);
+    let loaded = crate::parse_valid_schema_from_json(&crate::compile_schema(
+        &schema.to_string(),
+    ))?;
+    assert_eq!(loaded.to_string(), schema.to_string());
+    let table = loaded.get_table("T").unwrap();
+    assert_eq!(
+        table.get_column("b").unwrap().get_default_value(),
+        Some(&Literal::Long(3))
+    );
+    assert_eq!(
+        table.get_column("c").unwrap().get_default_value(),
+        Some(&Literal::Dbl(2.0))
+    );
+
+    // individual types can be loaded too
+    let json = serde_json::json!({"super": "int", "nullable": true, "min": 0, "max": null});
+    assert_eq!(IntDataType::from_json(&json)?.to_string(), "int<0, >?");
+
+    // malformed JSON is rejected
+    for json in [
+        serde_json::json!({"super": "int", "min": 0}),
+        serde_json::json!({"super": "int", "nullable": false, "min": "zero"}),
+        serde_json::json!({"super": "int", "nullable": false, "min": 3000000000i64}),
+        serde_json::json!({"super": "float", "nullable": false}),
+    ] {
+        assert!(SharedDataType::from_json(&json).is_err());
+    }
+    let json = serde_json::json!({"tables": {"T": {"columns": {"a": {"column_type": {"super": "int", "nullable": false}, "default_value": "x"}}}}});
+    assert!(crate::parse_valid_schema_from_json(&json).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_json_schema() -> anyhow::Result<()> {
+    let schema = crate::parse_valid_schema_from_str(
+        "table Users (id: int<1, >, score: dbl<0, 1>?, name: str<2, 32> ~ \"^[A-Za-z ]+$\", role: enum(\"admin\", \"user\")? = \"user\", joined: date, active: bool = true);",
+    )?;
+    let expected = serde_json::json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "Users",
+        "type": "object",
+        "properties": {
+            "id": {"type": "integer", "minimum": 1},
+            "score": {"type": ["number", "null"], "minimum": 0.0, "maximum": 1.0},
+            "name": {"type": "string", "minLength": 2, "maxLength": 32, "pattern": "^[A-Za-z ]+$"},
+            "role": {"enum": ["admin", "user", null], "default": "user"},
+            "joined": {"type": "string", "format": "date"},
+            "active": {"type": "boolean", "default": true}
+        },
+        "required": ["id", "name", "joined"],
+        "additionalProperties": false
+    });
+    assert_eq!(schema.to_json_schemas()["Users"], expected);
+
+    // decimals are strings whose digits are checked by the pattern
+    let schema =
+        crate::parse_valid_schema_from_str("table T (a: dec<5, 2>?);")?;
+    let a = &schema.to_json_schemas()["T"]["properties"]["a"];
+    assert_eq!(a["type"], serde_json::json!(["string", "null"]));
+    let pattern = regex::Regex::new(a["pattern"].as_str().unwrap())?;
+    for (text, valid) in [
+        ("123.45", true),
+        ("-0.5", true),
+        ("1.500", true),
+        ("1234.5", false),
+        ("1.234", false),
+    ] {
+        assert_eq!(pattern.is_match(text), valid, "{text}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_typescript() -> anyhow::Result<()> {
+    let schema = crate::parse_valid_schema_from_str(
+        "table Users (id: int<1, >, score: dbl<0, 1>?, name: str<2, 32> ~ \"^[a-z]+$\", role: enum(\"admin\", \"user\") = \"user\");",
+    )?;
+    let expected = r#"import { z } from "zod";
+
+export interface Users {
+  id: number;
+  score: number | null;
+  name: string;
+  role: "admin" | "user";
+}
+
+export const UsersSchema = z.object({
+  id: z.number().int().min(1),
+  score: z.number().min(0).max(1).nullable(),
+  name: z.string().min(2).max(32).regex(new RegExp("^[a-z]+$")),
+  role: z.enum(["admin", "user"]).default("user"),
+});
+"#;
+    assert_eq!(schema.to_typescript(), expected);
+
+    let schema = crate::parse_valid_schema_from_str(
+        "table \"Order Items\" (\"unit price\": dbl); table \"Order-Items\" (a: int); table \"class\" (b: str); table \"2nd\" (c: bool);",
+    )?;
+    let ts = schema.to_typescript();
+    assert!(
+        ts.contains(
+            "export interface Order_Items {\n  \"unit price\": number;"
+        )
+    );
+    assert!(ts.contains("export const Order_ItemsSchema = z.object"));
+    assert!(ts.contains("export interface Order_Items_2 {"));
+    assert!(ts.contains("export const Order_Items_2Schema = z.object"));
+    assert!(ts.contains("export interface class_ {"));
+    assert!(ts.contains("export const class_Schema = z.object"));
+    assert!(ts.contains("export interface _2nd {"));
+
+    // longs are bigints, so large bounds stay exact
+    let schema = crate::parse_valid_schema_from_str(
+        "table T (a: long<, 3000000000000000001>? = 5);",
+    )?;
+    let ts = schema.to_typescript();
+    assert!(ts.contains("  a: bigint | null;"));
+    assert!(ts.contains(
+        "  a: z.coerce.bigint().max(3000000000000000001n).nullable().default(5n),"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_rust_structs() -> anyhow::Result<()> {
+    let schema = crate::parse_valid_schema_from_str(
+        "table Users (id: int<1, >, self: str?, joined: date);",
+    )?;
+    let rust = schema.to_rust();
+    // the schema is embedded as JSON for `validate`
+    assert!(rust.contains(&format!("{:?}", schema.to_json().to_string())));
+
+    let expected = "pub struct Users {
+    pub id: i32,
+    #[serde(rename = \"self\")]
+    pub self_: ::std::option::Option<::std::string::String>,
+    pub joined: ::ftables_lib::chrono::NaiveDate,
+}";
+    assert!(rust.contains(expected));
+    syn::parse_file(&rust)?;
+    assert!(rust.contains(
+        "self.self_.as_deref().map(|val| ::ftables_lib::ql::lex::Literal::Str(val.into())),"
+    ));
+    assert!(
+        rust.contains(
+            "Some(::ftables_lib::ql::lex::Literal::Date(self.joined)),"
+        )
+    );
+
+    // quoted names are made into identifiers, and tables can share names
+    // with the types the structs use
+    let schema = crate::parse_valid_schema_from_str(
+        "table \"Order Items\" (\"unit price\": dbl, unit_price: int, \"2nd\": str?, \"type\": bool);
+        table Option (\"Order Items\": str);
+        table \"Order-Items\" (a: int);",
+    )?;
+    let rust = schema.to_rust();
+    syn::parse_file(&rust)?;
+    let expected = "pub struct Order_Items {
+    #[serde(rename = \"unit price\")]
+    pub unit_price: f64,
+    #[serde(rename = \"unit_price\")]
+    pub unit_price_2: i32,
+    #[serde(rename = \"2nd\")]
+    pub _2nd: ::std::option::Option<::std::string::String>,
+    pub r#type: bool,
+}";
+    assert!(rust.contains(expected));
+    assert!(rust.contains("pub struct Option {"));
+    assert!(rust.contains("pub struct Order_Items_2 {"));
+    assert!(rust.contains("schema.get_table(\"Order-Items\")"));
+    // the embedded schema still loads
+    crate::parse_valid_schema_from_json(&schema.to_json())?;
+
+    // the generated code compiles (see `generated_structs`) and validates
+    // rows against the schema it was generated from
+    let output = std::env::temp_dir().join("ftables_rust_structs.rs");
+    crate::core::schema::rust::generate_structs(
+        "test_artifacts/rust_structs/input.txt",
+        &output,
+    )?;
+    assert_eq!(
+        fs::read_to_string(&output)?,
+        fs::read_to_string("test_artifacts/rust_structs/output.rs")?
+    );
+
+    let mut user = generated_structs::Users {
+        id: 1,
+        self_: None,
+        joined: "2024-01-01".parse()?,
+        score: Some("1.25".parse()?),
+        role: "admin".into(),
+    };
+    assert!(user.validate().is_empty());
+    user.id = 0;
+    user.self_ = Some("".into());
+    user.score = Some("-1".parse()?);
+    user.role = "guest".into();
+    let found: Vec<String> = user
+        .validate()
+        .iter()
+        .map(|v| v.get_column().to_string())
+        .collect();
+    assert_eq!(found, vec!["id", "self", "role"]);
+
+    Ok(())
+}
+
+// the structs generated for test_artifacts/rust_structs/input.txt
+mod generated_structs {
+    include!("../test_artifacts/rust_structs/output.rs");
+}
+
+#[test]
+fn test_error_locations() -> anyhow::Result<()> {
+    let (tokens, spans) = lex_with_spans("table T (\n  a: int // comment\n);")?;
+    assert_eq!(tokens.len(), 8);
+    assert_eq!(spans[3], Span::new(12, 13));
+    assert_eq!(
+        spans[3].get_line_col("table T (\n  a: int // comment\n);"),
+        (2, 3)
+    );
+
+    let locate = |s: &str| {
+        let err = crate::parse_valid_schema_from_str(s).err().unwrap();
+        let err = err.downcast::<SourceError>().unwrap();
+        (
+            err.get_line(),
+            err.get_column(),
+            err.get_message().to_string(),
+        )
+    };
+
+    // parse errors point at the token the parser was up to
+    let schema = "table Users (\n  id: int<0, >,\n  name: str<2, 32\n);";
+    assert_eq!(locate(schema), (4, 1, "Couldn't get '>'".into()));
+    let err = crate::parse_valid_schema_from_str(schema).err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "line 4, column 1: Couldn't get '>'\n  |\n4 | );\n  | ^"
+    );
+    assert_eq!(
+        locate("table T (a: int, b: foo);"),
+        (1, 21, "Unrecognised type name foo".into())
+    );
+    assert_eq!(
+        locate("table T (a: int<0, \"x\">);"),
+        (1, 20, "Couldn't parse int literal!".into())
+    );
+    assert_eq!(
+        locate("table T (a: int, a: str);"),
+        (1, 18, "Can't have multiple columns named 'a'".into())
+    );
+    assert_eq!(
+        locate("table T (a: int)"),
+        (1, 17, "Couldn't get ';'".into())
+    );
+
+    // so do lex errors
+    assert_eq!(
+        locate("table T (\n  a: int #\n);"),
+        (2, 10, "Couldn't lex '#': unexpected character".into())
+    );
+    assert_eq!(
+        locate("table T (a: enum(\"x\", \"y));"),
+        (1, 23, "Couldn't lex '\"y));': unterminated string".into())
+    );
+    let err = crate::parse_valid_schema_from_str(
+        "table T (a: int<0, 99999999999999999999>);",
+    )
+    .err()
+    .unwrap();
+    assert!(matches!(
+        err.downcast_ref::<SchemaError>(),
+        Some(SchemaError::InvalidToken { text, .. }) if text == "99999999999999999999"
+    ));
+
+    // validation errors point at the definition they're about
+    let (line, column, message) = locate(
+        "type t int<5, 1>;\ntable T (\n  a: int,\n  b: int<0, 10> = 99\n);",
+    );
+    assert_eq!((line, column), (1, 6));
+    assert!(message.starts_with("Type 't'"));
+    assert_eq!(
+        locate("table T (a: int = 1);\ntable U (b: int references T(c));").0,
+        2
+    );
+    let err = crate::parse_valid_schema_from_str(
+        "table T (\n  a: int,\n  b: int<0, 10> = 99\n);",
+    )
+    .err()
+    .unwrap();
+    assert!(
+        err.to_string()
+            .ends_with("3 |   b: int<0, 10> = 99\n  |   ^")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_diagnostics() -> anyhow::Result<()> {
+    let s = "type unused int<0, >;
+table A (id: int<0, >, name: str<2, 1>);
+table B (x: int<0, \"a\">);
+table C (y: foo);
+table D (z: int = \"s\", w: int references A(nope));
+";
+    let (schema, diagnostics) = crate::check_schema(s);
+    assert!(schema.is_none());
+
+    let found: Vec<(Severity, usize)> = diagnostics
+        .iter()
+        .map(|d| (d.get_severity(), d.get_span().unwrap().get_line_col(s).0))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (Severity::Warning, 1),
+            (Severity::Error, 2),
+            (Severity::Error, 3),
+            (Severity::Error, 4),
+            (Severity::Error, 5),
+            (Severity::Error, 5),
+        ]
+    );
+    assert_eq!(diagnostics[3].get_message(), "Unrecognised type name foo");
+    assert_eq!(
+        diagnostics[0].render(s),
+        "warning: line 1, column 6: Type 'unused' is never used
+  |
+1 | type unused int<0, >;
+  |      ^^^^^^"
+    );
+
+    // a missing `;` doesn't swallow the next statement
+    let tq = lex("table T (a: int)\ntable U (b: int references T(a));")?;
+    let (schema, errors) = parse_spreadsheet_schema_with_recovery(&tq);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(schema.get_table_names().len(), 2);
+    assert!(schema.find_schema_errors().is_empty());
+
+    // lex errors are located, and checking carries on past them
+    let s = "table T (a: int #, b: str);\ntable U (c: enum(\"x);\ntable V (d: foo);";
+    let (schema, diagnostics) = crate::check_schema(s);
+    assert!(schema.is_none());
+    let found: Vec<(usize, usize, &str)> = diagnostics
+        .iter()
+        .map(|d| {
+            let (line, col) = d.get_span().unwrap().get_line_col(s);
+            (line, col, d.get_message())
+        })
+        .collect();
+    assert_eq!(found[0], (1, 17, "Couldn't lex '#': unexpected character"));
+    assert_eq!(
+        found[1],
+        (2, 18, "Couldn't lex '\"x);': unterminated string")
+    );
+    assert_eq!(
+        found.last().unwrap(),
+        &(3, 13, "Unrecognised type name foo")
+    );
+
+    // warnings don't stop the schema from being used
+    let (schema, diagnostics) =
+        crate::check_schema("type id int<1, >;\ntable T (a: int);");
+    assert!(schema.is_some());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].get_severity(), Severity::Warning);
+
+    Ok(())
+}
+
+#[test]
+fn test_schema_errors() -> anyhow::Result<()> {
+    let schema_error = |s: &str| {
+        let err = crate::parse_valid_schema_from_str(s).err().unwrap();
+        err.downcast_ref::<SchemaError>().unwrap().clone()
+    };
+
+    // validators report what was wrong with the value
+    let int_type = IntDataType::new(Some(0), Some(10), false);
+    assert_eq!(
+        int_type.validate_literal(None),
+        Err(SchemaError::NullViolation)
+    );
+    assert_eq!(
+        int_type.validate_literal(Some(&Literal::Int(11))),
+        Err(SchemaError::AboveMax {
+            max: "10".into(),
+            value: "11".into()
+        })
+    );
+    let err = int_type
+        .validate_literal(Some(&Literal::Bool(true)))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        SchemaError::TypeMismatch {
+            expected: "int".into(),
+            found: "true".into()
+        }
+    );
+    assert_eq!(
+        err.get_constraint_kind(),
+        Some(ConstraintKind::TypeMismatch)
+    );
+
+    // string lengths are counted in characters, not bytes
+    let str_type = StrDataType::new(Some(2), Some(3), None, false);
+    assert!(
+        str_type
+            .validate_literal(Some(&Literal::Str("日本語".into())))
+            .is_ok()
+    );
+    assert_eq!(
+        str_type.validate_literal(Some(&Literal::Str("é".into()))),
+        Err(SchemaError::TooShort { min: 2, len: 1 })
+    );
+
+    // parse errors keep their position alongside the typed error
+    let tq = lex("table T (a: int, b: foo);")?;
+    let err = parse_spreadsheet_schema(&tq).err().unwrap();
+    assert_eq!(err.get_idx(), 9);
+    assert_eq!(
+        err.get_error(),
+        &SchemaError::UnknownType { name: "foo".into() }
+    );
+    assert_eq!(
+        schema_error("type t int;\ntype t str;"),
+        SchemaError::DuplicateSymbol { name: "t".into() }
+    );
+    assert_eq!(
+        schema_error("table T (a: int)"),
+        SchemaError::UnexpectedToken {
+            expected: "';'".into(),
+            found: None
+        }
+    );
+
+    // so do validation errors, even once they're located in the source
+    let tq = lex("table T (a: int<5, 1>);")?;
+    let err = parse_spreadsheet_schema(&tq)?
+        .validate_spreadsheet_schema()
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "Column 'T.a': Can't have min (5) > max (1)"
+    );
+    assert_eq!(err.get_subject(), &Subject::Column("T".into(), "a".into()));
+    assert_eq!(
+        err.get_error(),
+        &SchemaError::InvalidBounds {
+            min: "5".into(),
+            max: "1".into()
+        }
+    );
+    assert_eq!(
+        schema_error("table T (a: int<0, 10> = 99);"),
+        SchemaError::InvalidDefault {
+            value: "99".into(),
+            error: Box::new(SchemaError::AboveMax {
+                max: "10".into(),
+                value: "99".into()
+            })
+        }
+    );
+    assert_eq!(
+        schema_error("table T (a: int references U(b));"),
+        SchemaError::UnknownTable {
+            column: "T.a".into(),
+            table: "U".into()
+        }
+    );
+
+    // checks that don't type-check have their own errors
+    assert_eq!(
+        schema_error("table T (a: int, check b > 0);"),
+        SchemaError::UnknownCheckColumn { column: "b".into() }
+    );
+    assert_eq!(
+        schema_error("table T (a: int, b: str, check a = b);"),
+        SchemaError::MismatchedOperands {
+            expr: "a = b".into(),
+            left: "Number".into(),
+            right: "Str".into()
+        }
+    );
+    assert_eq!(
+        schema_error("table T (a: int, check a + 1);"),
+        SchemaError::InvalidExprType {
+            expr: "a + 1".into(),
+            expected: "Bool".into(),
+            found: "Number".into()
+        }
+    );
+    assert_eq!(
+        schema_error("table T (a: bool, check -a);"),
+        SchemaError::InvalidExprType {
+            expr: "a".into(),
+            expected: "Number".into(),
+            found: "Bool".into()
+        }
+    );
+
+    // as do unknown types in JSON, and unknown columns in evaluated checks
+    let json = serde_json::json!({"super": "money", "nullable": false});
+    let err = SharedDataType::from_json(&json).err().unwrap();
+    assert_eq!(
+        err.downcast_ref::<SchemaError>(),
+        Some(&SchemaError::UnknownType {
+            name: "money".into()
+        })
+    );
+    let schema = crate::parse_valid_schema_from_str("table T (a: int);")?;
+    let table = schema.get_table("T").unwrap();
+    let err = Expr::Column("b".into())
+        .eval(&table, &vec![Some(Literal::Int(1))])
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.downcast_ref::<SchemaError>(),
+        Some(&SchemaError::UnknownCheckColumn { column: "b".into() })
+    );
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+/// Translates a data type to TypeScript, for generating client code.
+pub trait ToTypeScript {
+    /// The TypeScript type of a value of this type.
+    fn ts_type(&self) -> String;
+
+    /// A Zod schema that validates a value of this type.
+    fn zod_schema(&self) -> String;
+}
+
+/// Quotes a string as a TypeScript string literal.
+pub fn quote_ts_str(s: &str) -> String {
+    serde_json::Value::from(s).to_string()
+}
+
+/// Formats a property name, quoting it if it isn't a valid identifier.
+pub fn ts_property(name: &str) -> String {
+    let is_ident = name.chars().enumerate().all(|(i, c)| {
+        c == '_'
+            || c == '$'
+            || c.is_ascii_alphabetic()
+            || (i > 0 && c.is_ascii_digit())
+    });
+    match is_ident && !name.is_empty() {
+        true => name.to_string(),
+        false => quote_ts_str(name),
+    }
+}
+
+// Words that can't name an interface: reserved words and the predefined type
+// names.
+const TS_RESERVED: [&str; 47] = [
+    "any",
+    "bigint",
+    "boolean",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "import",
+    "in",
+    "instanceof",
+    "never",
+    "new",
+    "null",
+    "number",
+    "object",
+    "return",
+    "string",
+    "super",
+    "switch",
+    "symbol",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "undefined",
+    "unknown",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+/// Formats a name as a TypeScript identifier. Characters that can't appear
+/// in one are replaced with underscores, names starting with a digit get one
+/// prepended, and reserved words get one appended.
+pub fn ts_ident(name: &str) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() || c == '$' {
+            true => c,
+            false => '_',
+        })
+        .collect();
+    if !ident.starts_with(|c: char| !c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    match TS_RESERVED.contains(&ident.as_str()) {
+        true => ident + "_",
+        false => ident,
+    }
+}
+
+/// Formats each of `names` as a distinct TypeScript identifier, numbering
+/// any that would otherwise be the same.
+pub fn ts_idents(names: &[impl AsRef<str>]) -> Vec<String> {
+    let mut idents: Vec<String> = Vec::new();
+    for name in names {
+        let base = ts_ident(name.as_ref());
+        let mut ident = base.clone();
+        let mut n = 1;
+        while idents.contains(&ident) {
+            n += 1;
+            ident = format!("{base}_{n}");
+        }
+        idents.push(ident);
+    }
+    idents
+}
@@ -0,0 +1,34 @@
+// Generated by ftables_lib. Do not edit.
+
+thread_local! {
+    static FTABLES_SCHEMA: ::ftables_lib::core::schema::SpreadsheetSchema =
+        ::ftables_lib::parse_valid_schema_from_json(
+            &::ftables_lib::serde_json::from_str("{\"types\":{\"Role\":{\"super\":\"enum\",\"nullable\":false,\"variants\":[\"admin\",\"user\"]}},\"tables\":{\"Users\":{\"columns\":{\"id\":{\"column_type\":{\"super\":\"int\",\"nullable\":false,\"min\":1,\"max\":null}},\"self\":{\"column_type\":{\"super\":\"str\",\"nullable\":true,\"min\":1,\"max\":32,\"pattern\":null}},\"joined\":{\"column_type\":{\"super\":\"date\",\"nullable\":false,\"min\":null,\"max\":null}},\"score\":{\"column_type\":{\"super\":\"dec\",\"nullable\":true,\"precision\":4,\"scale\":2}},\"role\":{\"column_type\":{\"super\":\"enum\",\"nullable\":false,\"variants\":[\"admin\",\"user\"]},\"type_alias\":\"Role\",\"default_value\":\"user\"}},\"primary_key\":null,\"unique\":[],\"checks\":[\"score >= 0 or score is null\"]}}}").unwrap(),
+        )
+        .unwrap();
+}
+
+#[derive(Clone, Debug, PartialEq, ::ftables_lib::serde::Serialize, ::ftables_lib::serde::Deserialize)]
+#[serde(crate = "::ftables_lib::serde")]
+pub struct Users {
+    pub id: i32,
+    #[serde(rename = "self")]
+    pub self_: ::std::option::Option<::std::string::String>,
+    pub joined: ::ftables_lib::chrono::NaiveDate,
+    pub score: ::std::option::Option<::ftables_lib::rust_decimal::Decimal>,
+    pub role: ::std::string::String,
+}
+
+impl Users {
+    /// Checks the row against the constraints on each column of `Users`.
+    pub fn validate(&self) -> ::std::vec::Vec<::ftables_lib::core::schema::violation::CellViolation> {
+        let row = [
+            Some(::ftables_lib::ql::lex::Literal::Int(self.id)),
+            self.self_.as_deref().map(|val| ::ftables_lib::ql::lex::Literal::Str(val.into())),
+            Some(::ftables_lib::ql::lex::Literal::Date(self.joined)),
+            self.score.map(::ftables_lib::ql::lex::Literal::Dec),
+            Some(::ftables_lib::ql::lex::Literal::Str(self.role.as_str().into())),
+        ];
+        FTABLES_SCHEMA.with(|schema| schema.get_table("Users").unwrap().validate_row(&row))
+    }
+}